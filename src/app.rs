@@ -1,15 +1,30 @@
 #![warn(clippy::pedantic)]
-use chrono::{DateTime, Duration, Local, LocalResult, NaiveTime, TimeZone};
+use chrono::{DateTime, Duration, Local, NaiveTime};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 use thiserror::Error;
+use ulid::Ulid;
 
-use crate::key_utils::{key_to_string, string_to_key};
+use crate::clock::{
+    clock_drift, interpolate_hacks, ClockHealth, ClockSource, HackTime, SystemClock, TimeHack,
+};
+use crate::export::ExportFormat;
+use crate::file_lock::LockedFile;
+use crate::history::{History, SessionState, Step, TimeHackState};
+use crate::journal::{Change, Journal, Orphan};
+use crate::key_utils::{key_to_string, string_to_key, KeyCombo};
+use crate::remote::{Inbox, RemoteCommand, RemoteEvent};
+use crate::status::{StatusKind, StatusMessage};
+use crate::stream::EventStream;
+use crate::zone::DisplayZone;
+use crate::FileFormat;
 use crate::ThemeName;
 
 fn default_theme_key() -> String {
@@ -24,20 +39,157 @@ fn default_file_key() -> String {
     key_to_string(KeyCode::Char('f'))
 }
 
+fn default_mark_key() -> String {
+    key_to_string(KeyCode::Char('m'))
+}
+
+fn default_describe_key() -> String {
+    key_to_string(KeyCode::Char('p'))
+}
+
+fn default_delete_key() -> String {
+    key_to_string(KeyCode::Char('x'))
+}
+
+fn default_move_up_key() -> String {
+    key_to_string(KeyCode::Char('K'))
+}
+
+fn default_move_down_key() -> String {
+    key_to_string(KeyCode::Char('J'))
+}
+
+fn default_insert_above_key() -> String {
+    key_to_string(KeyCode::Char('O'))
+}
+
+fn default_insert_below_key() -> String {
+    key_to_string(KeyCode::Char('o'))
+}
+
+fn default_undo_key() -> String {
+    key_to_string(KeyCode::Char('u'))
+}
+
+fn default_redo_key() -> String {
+    key_to_string(KeyCode::Char('r'))
+}
+
+fn default_edit_time_key() -> String {
+    key_to_string(KeyCode::Char('T'))
+}
+
+fn default_metadata_key() -> String {
+    key_to_string(KeyCode::Char('i'))
+}
+
+/// Returns the time hack in effect after `entries`, according to the last
+/// note recording a time hack or a reset.
+fn last_time_hack(entries: &[Entry]) -> Option<TimeHack> {
+    entries
+        .iter()
+        .rev()
+        .find_map(|e| match e {
+            Entry::Note(n) if n.time_hack.is_some() || n.hack_reset => Some(n.time_hack),
+            _ => None,
+        })
+        .flatten()
+}
+
 /// Represents a single note with timestamp.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Note {
+    /// Identifier that stays with the note through edits, saves and merges.
+    ///
+    /// Files written before IDs existed are given IDs derived from their
+    /// contents when read, see [`Entry::id`].
+    #[serde(default)]
+    pub id: Ulid,
     /// The time the note was started.
+    #[serde(with = "crate::zone::utc")]
     pub timestamp: DateTime<Local>,
     /// The contents of the note.
     pub text: String,
+    /// Whether the note is a captured timestamp still awaiting a description.
+    #[serde(default)]
+    pub pending: bool,
+    /// Time originally captured for the note if the timestamp was corrected.
+    #[serde(default, with = "crate::zone::utc::option")]
+    pub original_timestamp: Option<DateTime<Local>>,
+    /// Program that created the note, if it was not typed by the operator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The time hack this note records, if it was created by one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_hack: Option<TimeHack>,
+    /// Whether this note records returning to the clock source after a time hack.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hack_reset: bool,
+}
+
+impl Note {
+    /// Creates a described note with the given timestamp and text.
+    #[must_use]
+    pub fn new(timestamp: DateTime<Local>, text: impl Into<String>) -> Self {
+        Self {
+            id: Ulid::generate(),
+            timestamp,
+            text: text.into(),
+            pending: false,
+            original_timestamp: None,
+            source: None,
+            time_hack: None,
+            hack_reset: false,
+        }
+    }
+
+    /// Creates a placeholder note for a timestamp that has not been described yet.
+    #[must_use]
+    pub fn pending(timestamp: DateTime<Local>) -> Self {
+        Self {
+            id: Ulid::generate(),
+            timestamp,
+            text: String::new(),
+            pending: true,
+            original_timestamp: None,
+            source: None,
+            time_hack: None,
+            hack_reset: false,
+        }
+    }
+
+    /// Moves the note to a new timestamp, remembering the first captured time.
+    pub fn retime(&mut self, timestamp: DateTime<Local>) {
+        if timestamp != self.timestamp {
+            self.original_timestamp.get_or_insert(self.timestamp);
+            self.timestamp = timestamp;
+        }
+    }
 }
 
 /// Represents a section with a title but no timestamp.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Section {
+    /// Identifier that stays with the section through edits, saves and merges.
+    #[serde(default)]
+    pub id: Ulid,
     /// The section title.
     pub title: String,
+    /// Program that created the section, if it was not typed by the operator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl Section {
+    /// Creates a section with the given title.
+    #[must_use]
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            id: Ulid::generate(),
+            title: title.into(),
+            source: None,
+        }
+    }
 }
 
 /// A single entry which may be a note or a section.
@@ -49,6 +201,76 @@ pub enum Entry {
     Section(Section),
 }
 
+impl Entry {
+    /// Returns the persistent identifier of the note or section.
+    #[must_use]
+    pub fn id(&self) -> Ulid {
+        match self {
+            Self::Note(n) => n.id,
+            Self::Section(s) => s.id,
+        }
+    }
+}
+
+/// Descriptive information about a recording session.
+///
+/// Stored as a header block at the top of saved files so a log can be traced
+/// back to the test, vehicle and operator it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionMetadata {
+    /// Short title of the session, such as the test point name.
+    pub title: String,
+    /// Person taking the notes.
+    pub operator: String,
+    /// Time the session was started.
+    #[serde(with = "crate::zone::utc")]
+    pub created: DateTime<Local>,
+    /// Time source in use when the file was last saved, if known.
+    pub time_source: Option<String>,
+    /// Free-form key/value fields in display order.
+    pub fields: Vec<(String, String)>,
+}
+
+impl Default for SessionMetadata {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            operator: String::new(),
+            created: Local::now(),
+            time_source: None,
+            fields: Vec::new(),
+        }
+    }
+}
+
+impl SessionMetadata {
+    /// Returns the value of a free-form field.
+    #[must_use]
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Sets a free-form field, replacing any existing value.
+    ///
+    /// An empty value removes the field.
+    pub fn set_field(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        let pos = self.fields.iter().position(|(k, _)| *k == key);
+        match (pos, value.is_empty()) {
+            (Some(i), true) => {
+                self.fields.remove(i);
+            }
+            (Some(i), false) => self.fields[i].1 = value,
+            (None, false) => self.fields.push((key, value)),
+            (None, true) => {}
+        }
+    }
+}
+
 /// Input mode for the application.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputMode {
@@ -82,6 +304,20 @@ pub enum InputMode {
     BindWarning,
     /// Selecting a color theme.
     ThemeSelect,
+    /// Offering to restore entries from an unclean session journal.
+    RecoverSession,
+    /// Confirming deletion of the selected entry.
+    ConfirmDelete,
+    /// Editing the timestamp of an existing note.
+    EditingTimestamp,
+    /// Browsing the session metadata overlay.
+    Metadata,
+    /// Editing the selected session metadata field.
+    EditingMetadata,
+    /// Asking whether to save unsaved changes before quitting.
+    ConfirmQuit,
+    /// Prompting for a file path to export the log to.
+    Exporting,
 }
 
 /// Actions that can be bound to keys.
@@ -102,10 +338,32 @@ pub enum Action {
     Theme,
     /// Open the Time Hack input.
     TimeHack,
+    /// Capture the current time as a pending note without opening the editor.
+    Mark,
+    /// Describe the oldest pending note.
+    Describe,
+    /// Delete the selected entry after confirmation.
+    Delete,
+    /// Move the selected entry up.
+    MoveUp,
+    /// Move the selected entry down.
+    MoveDown,
+    /// Insert a section above the selected entry.
+    InsertAbove,
+    /// Insert a section below the selected entry.
+    InsertBelow,
+    /// Undo the most recent change to the entries.
+    Undo,
+    /// Redo the most recently undone change.
+    Redo,
+    /// Correct the timestamp of the selected note.
+    EditTime,
+    /// Open the session metadata overlay.
+    Metadata,
 }
 
 impl Action {
-    pub const ALL: [Action; 13] = [
+    pub const ALL: [Action; 24] = [
         Action::Up,
         Action::Down,
         Action::Edit,
@@ -119,6 +377,17 @@ impl Action {
         Action::Bindings,
         Action::Theme,
         Action::TimeHack,
+        Action::Mark,
+        Action::Describe,
+        Action::Delete,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::InsertAbove,
+        Action::InsertBelow,
+        Action::Undo,
+        Action::Redo,
+        Action::EditTime,
+        Action::Metadata,
     ];
 }
 
@@ -138,6 +407,17 @@ impl std::fmt::Display for Action {
             Action::Bindings => "Key Menu",
             Action::Theme => "Theme Menu",
             Action::TimeHack => "Time Hack",
+            Action::Mark => "Mark Time",
+            Action::Describe => "Describe Pending",
+            Action::Delete => "Delete",
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::InsertAbove => "Insert Above",
+            Action::InsertBelow => "Insert Below",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::EditTime => "Edit Time",
+            Action::Metadata => "Session Info",
         };
         write!(f, "{name}")
     }
@@ -147,49 +427,82 @@ impl std::fmt::Display for Action {
 #[derive(Debug, Clone)]
 pub struct KeyBindings {
     /// Move selection up or previous.
-    pub up: KeyCode,
+    pub up: KeyCombo,
     /// Move selection down or next.
-    pub down: KeyCode,
+    pub down: KeyCombo,
     /// Edit the currently selected entry.
-    pub edit: KeyCode,
+    pub edit: KeyCombo,
     /// Start entering a new note.
-    pub new_note: KeyCode,
+    pub new_note: KeyCombo,
     /// Start entering a new section.
-    pub new_section: KeyCode,
+    pub new_section: KeyCombo,
     /// Begin saving entries to a file.
-    pub save: KeyCode,
+    pub save: KeyCombo,
     /// Begin loading entries from a file.
-    pub load: KeyCode,
+    pub load: KeyCombo,
     /// Open the file management menu.
-    pub file_menu: KeyCode,
+    pub file_menu: KeyCombo,
     /// Quit the application.
-    pub quit: KeyCode,
+    pub quit: KeyCombo,
     /// Cancel the current edit.
-    pub cancel: KeyCode,
+    pub cancel: KeyCombo,
     /// Open the key bindings menu.
-    pub bindings: KeyCode,
+    pub bindings: KeyCombo,
     /// Open the theme selection menu.
-    pub theme: KeyCode,
+    pub theme: KeyCombo,
     /// Open the time hack input.
-    pub time_hack: KeyCode,
+    pub time_hack: KeyCombo,
+    /// Capture a pending timestamp.
+    pub mark: KeyCombo,
+    /// Describe the oldest pending timestamp.
+    pub describe: KeyCombo,
+    /// Delete the selected entry.
+    pub delete: KeyCombo,
+    /// Move the selected entry up.
+    pub move_up: KeyCombo,
+    /// Move the selected entry down.
+    pub move_down: KeyCombo,
+    /// Insert a section above the selected entry.
+    pub insert_above: KeyCombo,
+    /// Insert a section below the selected entry.
+    pub insert_below: KeyCombo,
+    /// Undo the most recent change.
+    pub undo: KeyCombo,
+    /// Redo the most recently undone change.
+    pub redo: KeyCombo,
+    /// Correct the timestamp of the selected note.
+    pub edit_time: KeyCombo,
+    /// Key to edit session metadata.
+    pub metadata: KeyCombo,
 }
 
 impl Default for KeyBindings {
     fn default() -> Self {
         Self {
-            up: KeyCode::Up,
-            down: KeyCode::Down,
-            edit: KeyCode::Char('e'),
-            new_note: KeyCode::Enter,
-            new_section: KeyCode::Char('s'),
-            save: KeyCode::Char('w'),
-            load: KeyCode::Char('l'),
-            file_menu: KeyCode::Char('f'),
-            quit: KeyCode::Char('q'),
-            cancel: KeyCode::Esc,
-            bindings: KeyCode::Char('b'),
-            theme: KeyCode::Char('t'),
-            time_hack: KeyCode::Char('h'),
+            up: KeyCode::Up.into(),
+            down: KeyCode::Down.into(),
+            edit: KeyCode::Char('e').into(),
+            new_note: KeyCode::Enter.into(),
+            new_section: KeyCode::Char('s').into(),
+            save: KeyCode::Char('w').into(),
+            load: KeyCode::Char('l').into(),
+            file_menu: KeyCode::Char('f').into(),
+            quit: KeyCode::Char('q').into(),
+            cancel: KeyCode::Esc.into(),
+            bindings: KeyCode::Char('b').into(),
+            theme: KeyCode::Char('t').into(),
+            time_hack: KeyCode::Char('h').into(),
+            mark: KeyCode::Char('m').into(),
+            describe: KeyCode::Char('p').into(),
+            delete: KeyCode::Char('x').into(),
+            move_up: KeyCode::Char('K').into(),
+            move_down: KeyCode::Char('J').into(),
+            insert_above: KeyCode::Char('O').into(),
+            insert_below: KeyCode::Char('o').into(),
+            undo: KeyCode::Char('u').into(),
+            redo: KeyCode::Char('r').into(),
+            edit_time: KeyCode::Char('T').into(),
+            metadata: KeyCode::Char('i').into(),
         }
     }
 }
@@ -247,9 +560,9 @@ impl KeyBindings {
     /// * `action` - The [`Action`] to query.
     ///
     /// # Returns
-    /// The [`KeyCode`] assigned to the action.
+    /// The [`KeyCombo`] assigned to the action.
     #[must_use]
-    pub fn get(&self, action: Action) -> KeyCode {
+    pub fn get(&self, action: Action) -> KeyCombo {
         match action {
             Action::Up => self.up,
             Action::Down => self.down,
@@ -264,6 +577,17 @@ impl KeyBindings {
             Action::Bindings => self.bindings,
             Action::Theme => self.theme,
             Action::TimeHack => self.time_hack,
+            Action::Mark => self.mark,
+            Action::Describe => self.describe,
+            Action::Delete => self.delete,
+            Action::MoveUp => self.move_up,
+            Action::MoveDown => self.move_down,
+            Action::InsertAbove => self.insert_above,
+            Action::InsertBelow => self.insert_below,
+            Action::Undo => self.undo,
+            Action::Redo => self.redo,
+            Action::EditTime => self.edit_time,
+            Action::Metadata => self.metadata,
         }
     }
 
@@ -271,8 +595,9 @@ impl KeyBindings {
     ///
     /// # Arguments
     /// * `action` - Action to modify.
-    /// * `key` - New key code.
-    pub fn set(&mut self, action: Action, key: KeyCode) {
+    /// * `key` - New key code or combo.
+    pub fn set(&mut self, action: Action, key: impl Into<KeyCombo>) {
+        let key = key.into();
         match action {
             Action::Up => self.up = key,
             Action::Down => self.down = key,
@@ -287,6 +612,17 @@ impl KeyBindings {
             Action::Bindings => self.bindings = key,
             Action::Theme => self.theme = key,
             Action::TimeHack => self.time_hack = key,
+            Action::Mark => self.mark = key,
+            Action::Describe => self.describe = key,
+            Action::Delete => self.delete = key,
+            Action::MoveUp => self.move_up = key,
+            Action::MoveDown => self.move_down = key,
+            Action::InsertAbove => self.insert_above = key,
+            Action::InsertBelow => self.insert_below = key,
+            Action::Undo => self.undo = key,
+            Action::Redo => self.redo = key,
+            Action::EditTime => self.edit_time = key,
+            Action::Metadata => self.metadata = key,
         }
     }
 
@@ -298,7 +634,8 @@ impl KeyBindings {
     /// # Returns
     /// `Some(Action)` if the key is bound, otherwise `None`.
     #[must_use]
-    pub fn action_for_key(&self, key: KeyCode) -> Option<Action> {
+    pub fn action_for_key(&self, key: impl Into<KeyCombo>) -> Option<Action> {
+        let key = key.into();
         if self.up == key {
             Some(Action::Up)
         } else if self.down == key {
@@ -325,6 +662,28 @@ impl KeyBindings {
             Some(Action::Theme)
         } else if self.time_hack == key {
             Some(Action::TimeHack)
+        } else if self.mark == key {
+            Some(Action::Mark)
+        } else if self.describe == key {
+            Some(Action::Describe)
+        } else if self.delete == key {
+            Some(Action::Delete)
+        } else if self.move_up == key {
+            Some(Action::MoveUp)
+        } else if self.move_down == key {
+            Some(Action::MoveDown)
+        } else if self.insert_above == key {
+            Some(Action::InsertAbove)
+        } else if self.insert_below == key {
+            Some(Action::InsertBelow)
+        } else if self.undo == key {
+            Some(Action::Undo)
+        } else if self.redo == key {
+            Some(Action::Redo)
+        } else if self.edit_time == key {
+            Some(Action::EditTime)
+        } else if self.metadata == key {
+            Some(Action::Metadata)
         } else {
             None
         }
@@ -349,6 +708,28 @@ struct KeyBindingsConfig {
     theme: String,
     #[serde(default = "default_time_hack_key")]
     time_hack: String,
+    #[serde(default = "default_mark_key")]
+    mark: String,
+    #[serde(default = "default_describe_key")]
+    describe: String,
+    #[serde(default = "default_delete_key")]
+    delete: String,
+    #[serde(default = "default_move_up_key")]
+    move_up: String,
+    #[serde(default = "default_move_down_key")]
+    move_down: String,
+    #[serde(default = "default_insert_above_key")]
+    insert_above: String,
+    #[serde(default = "default_insert_below_key")]
+    insert_below: String,
+    #[serde(default = "default_undo_key")]
+    undo: String,
+    #[serde(default = "default_redo_key")]
+    redo: String,
+    #[serde(default = "default_edit_time_key")]
+    edit_time: String,
+    #[serde(default = "default_metadata_key")]
+    metadata: String,
 }
 
 impl From<KeyBindings> for KeyBindingsConfig {
@@ -367,6 +748,17 @@ impl From<KeyBindings> for KeyBindingsConfig {
             bindings: key_to_string(k.bindings),
             theme: key_to_string(k.theme),
             time_hack: key_to_string(k.time_hack),
+            mark: key_to_string(k.mark),
+            describe: key_to_string(k.describe),
+            delete: key_to_string(k.delete),
+            move_up: key_to_string(k.move_up),
+            move_down: key_to_string(k.move_down),
+            insert_above: key_to_string(k.insert_above),
+            insert_below: key_to_string(k.insert_below),
+            undo: key_to_string(k.undo),
+            redo: key_to_string(k.redo),
+            edit_time: key_to_string(k.edit_time),
+            metadata: key_to_string(k.metadata),
         }
     }
 }
@@ -374,23 +766,38 @@ impl From<KeyBindings> for KeyBindingsConfig {
 impl From<KeyBindingsConfig> for KeyBindings {
     fn from(c: KeyBindingsConfig) -> Self {
         Self {
-            up: string_to_key(&c.up).unwrap_or(KeyCode::Up),
-            down: string_to_key(&c.down).unwrap_or(KeyCode::Down),
-            edit: string_to_key(&c.edit).unwrap_or(KeyCode::Char('e')),
-            new_note: string_to_key(&c.new_note).unwrap_or(KeyCode::Enter),
-            new_section: string_to_key(&c.new_section).unwrap_or(KeyCode::Char('s')),
-            save: string_to_key(&c.save).unwrap_or(KeyCode::Char('w')),
-            load: string_to_key(&c.load).unwrap_or(KeyCode::Char('l')),
-            file_menu: string_to_key(&c.file_menu).unwrap_or(KeyCode::Char('f')),
-            quit: string_to_key(&c.quit).unwrap_or(KeyCode::Char('q')),
-            cancel: string_to_key(&c.cancel).unwrap_or(KeyCode::Esc),
-            bindings: string_to_key(&c.bindings).unwrap_or(KeyCode::Char('b')),
-            theme: string_to_key(&c.theme).unwrap_or(KeyCode::Char('t')),
-            time_hack: string_to_key(&c.time_hack).unwrap_or(KeyCode::Char('h')),
+            up: string_to_key(&c.up).unwrap_or(KeyCombo::plain(KeyCode::Up)),
+            down: string_to_key(&c.down).unwrap_or(KeyCombo::plain(KeyCode::Down)),
+            edit: string_to_key(&c.edit).unwrap_or(KeyCombo::plain(KeyCode::Char('e'))),
+            new_note: string_to_key(&c.new_note).unwrap_or(KeyCombo::plain(KeyCode::Enter)),
+            new_section: string_to_key(&c.new_section)
+                .unwrap_or(KeyCombo::plain(KeyCode::Char('s'))),
+            save: string_to_key(&c.save).unwrap_or(KeyCombo::plain(KeyCode::Char('w'))),
+            load: string_to_key(&c.load).unwrap_or(KeyCombo::plain(KeyCode::Char('l'))),
+            file_menu: string_to_key(&c.file_menu).unwrap_or(KeyCombo::plain(KeyCode::Char('f'))),
+            quit: string_to_key(&c.quit).unwrap_or(KeyCombo::plain(KeyCode::Char('q'))),
+            cancel: string_to_key(&c.cancel).unwrap_or(KeyCombo::plain(KeyCode::Esc)),
+            bindings: string_to_key(&c.bindings).unwrap_or(KeyCombo::plain(KeyCode::Char('b'))),
+            theme: string_to_key(&c.theme).unwrap_or(KeyCombo::plain(KeyCode::Char('t'))),
+            time_hack: string_to_key(&c.time_hack).unwrap_or(KeyCombo::plain(KeyCode::Char('h'))),
+            mark: string_to_key(&c.mark).unwrap_or(KeyCombo::plain(KeyCode::Char('m'))),
+            describe: string_to_key(&c.describe).unwrap_or(KeyCombo::plain(KeyCode::Char('p'))),
+            delete: string_to_key(&c.delete).unwrap_or(KeyCombo::plain(KeyCode::Char('x'))),
+            move_up: string_to_key(&c.move_up).unwrap_or(KeyCombo::plain(KeyCode::Char('K'))),
+            move_down: string_to_key(&c.move_down).unwrap_or(KeyCombo::plain(KeyCode::Char('J'))),
+            insert_above: string_to_key(&c.insert_above)
+                .unwrap_or(KeyCombo::plain(KeyCode::Char('O'))),
+            insert_below: string_to_key(&c.insert_below)
+                .unwrap_or(KeyCombo::plain(KeyCode::Char('o'))),
+            undo: string_to_key(&c.undo).unwrap_or(KeyCombo::plain(KeyCode::Char('u'))),
+            redo: string_to_key(&c.redo).unwrap_or(KeyCombo::plain(KeyCode::Char('r'))),
+            edit_time: string_to_key(&c.edit_time).unwrap_or(KeyCombo::plain(KeyCode::Char('T'))),
+            metadata: string_to_key(&c.metadata).unwrap_or(KeyCombo::plain(KeyCode::Char('i'))),
         }
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for InputMode {
     fn default() -> Self {
         Self::Normal
@@ -403,6 +810,59 @@ pub enum AppError {
     /// I/O error.
     #[error(transparent)]
     Io(#[from] io::Error),
+    /// The file was written with a newer file format than this build reads.
+    #[error("file format version {found} is newer than the supported version {supported}; upgrade rtnt to open it")]
+    UnsupportedVersion {
+        /// Version recorded in the file.
+        found: u32,
+        /// Newest version this build understands.
+        supported: u32,
+    },
+    /// A record in the file could not be understood.
+    #[error("{}:{line}: {message}", path.display())]
+    Parse {
+        /// File being read.
+        path: PathBuf,
+        /// One-based line number of the offending record.
+        line: u64,
+        /// Description of the problem.
+        message: String,
+    },
+    /// The file or its directory may not be accessed.
+    #[error("permission denied: {}", .0.display())]
+    PermissionDenied(PathBuf),
+    /// The file does not exist.
+    #[error("file not found: {}", .0.display())]
+    NotFound(PathBuf),
+    /// The format of a file could not be told from its extension.
+    #[error("unknown file format: {}", .0.display())]
+    UnknownFormat(PathBuf),
+}
+
+impl AppError {
+    /// Classifies an I/O error raised while accessing `path`.
+    pub(crate) fn from_io(error: io::Error, path: &Path) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::NotFound(path.to_path_buf()),
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(path.to_path_buf()),
+            _ => Self::Io(error),
+        }
+    }
+}
+
+/// Number of options in the file menu: new, save, load, one per export format
+/// and re-timing notes between time hacks.
+const FILE_MENU_LEN: usize = 4 + ExportFormat::ALL.len();
+
+/// Progress of a request to quit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuitState {
+    /// The application keeps running.
+    Running,
+    /// Quit once the save prompt completes successfully.
+    AfterSave,
+    /// The application should exit.
+    Done,
 }
 
 /// Main application state for the note taking application.
@@ -423,12 +883,16 @@ pub struct App {
     mode: InputMode,
     /// Timestamp captured when note editing started.
     note_time: Option<DateTime<Local>>,
-    /// Active time hack offset.
-    time_hack: Option<(NaiveTime, Instant)>,
+    /// Source of the current time.
+    clock: Box<dyn ClockSource>,
+    /// Active manual correction of the clock source.
+    time_hack: Option<TimeHack>,
     /// Selected entry index when navigating.
     selected: Option<usize>,
-    /// Index of the entry currently being edited if editing an existing entry.
-    edit_index: Option<usize>,
+    /// ID of the entry currently being edited if editing an existing entry.
+    edit_id: Option<Ulid>,
+    /// Position where a new section is inserted instead of appending it.
+    insert_index: Option<usize>,
     /// Key bindings controlling the application.
     pub keys: KeyBindings,
     /// Directory used for saving and loading files.
@@ -444,7 +908,7 @@ pub struct App {
     /// Action being re-bound when capturing a key.
     pub capture_action: Option<Action>,
     /// Key to assign after confirmation.
-    pub pending_key: Option<KeyCode>,
+    pub pending_key: Option<KeyCombo>,
     /// Action being assigned during confirmation.
     pub pending_action: Option<Action>,
     /// Existing action that currently uses the pending key.
@@ -453,6 +917,45 @@ pub struct App {
     pub theme: ThemeName,
     /// Selected index when choosing a theme.
     pub theme_selected: usize,
+    /// Write-ahead journal receiving every entry mutation.
+    journal: Option<Journal>,
+    /// Entries replayed from an unclean journal awaiting a restore decision.
+    recovered: Option<Vec<Entry>>,
+    /// Journal the [`recovered`](Self::recovered) entries were read from,
+    /// kept locked until the decision is made.
+    orphan: Option<Orphan>,
+    /// Undo and redo stacks for entry mutations.
+    history: History,
+    /// Information about the session saved alongside the entries.
+    pub metadata: SessionMetadata,
+    /// Selected row in the session metadata overlay.
+    pub metadata_selected: usize,
+    /// Outcome of the last operation shown in the status line.
+    status: Option<StatusMessage>,
+    /// Whether entries or metadata changed since the last save or load.
+    dirty: bool,
+    /// File most recently saved to or loaded from.
+    file_path: Option<PathBuf>,
+    /// IDs of the entries last read from or written to
+    /// [`file_path`](Self::file_path), used to spot entries other programs
+    /// added since.
+    saved_ids: HashSet<Ulid>,
+    /// Save to [`file_path`](Self::file_path) without asking when quitting.
+    save_on_quit: bool,
+    /// Progress of a request to quit.
+    quit: QuitState,
+    /// Selected option in the quit confirmation overlay.
+    pub quit_selected: usize,
+    /// Commands received from other programs, waiting to be applied.
+    remote: Inbox,
+    /// Destination for a live feed of changes to the log.
+    stream: Option<EventStream>,
+    /// Format used by the export prompt.
+    pub export_format: ExportFormat,
+    /// Zone timestamps are shown in and typed times are read in.
+    pub display_zone: DisplayZone,
+    /// Zone of a second clock shown next to the first in the status line.
+    pub second_zone: Option<DisplayZone>,
 }
 
 impl Default for App {
@@ -466,9 +969,11 @@ impl Default for App {
             cursor: 0,
             mode: InputMode::Normal,
             note_time: None,
+            clock: Box::new(SystemClock),
             time_hack: None,
             selected: None,
-            edit_index: None,
+            edit_id: None,
+            insert_index: None,
             keys: KeyBindings::load_or_default(),
             save_dir,
             load_files: Vec::new(),
@@ -481,6 +986,24 @@ impl Default for App {
             pending_conflict: None,
             theme: ThemeName::load_or_default(),
             theme_selected: 0,
+            journal: None,
+            recovered: None,
+            orphan: None,
+            history: History::default(),
+            metadata: SessionMetadata::default(),
+            metadata_selected: 0,
+            status: None,
+            dirty: false,
+            file_path: None,
+            saved_ids: HashSet::new(),
+            save_on_quit: false,
+            quit: QuitState::Running,
+            quit_selected: 0,
+            export_format: ExportFormat::default(),
+            display_zone: DisplayZone::default(),
+            second_zone: None,
+            remote: Inbox::default(),
+            stream: None,
         }
     }
 }
@@ -619,53 +1142,61 @@ impl App {
     }
 
     /// Returns the active time hack if set.
-    ///
-    /// # Returns
-    /// `Some((NaiveTime, Instant))` when time hacking is enabled.
     #[must_use]
-    pub fn time_hack(&self) -> Option<(NaiveTime, Instant)> {
+    pub fn time_hack(&self) -> Option<TimeHack> {
         self.time_hack
     }
 
-    /// Returns the current timestamp considering any active time hack.
+    /// Replaces the source of the current time.
     ///
-    /// When a time hack is active, the returned time will be calculated by
-    /// adding the elapsed time since the hack started to the hacked base time.
+    /// Any active time hack is cleared, since it was measured against the
+    /// previous source.
+    pub fn set_clock(&mut self, clock: impl ClockSource + 'static) {
+        self.clock = Box::new(clock);
+        self.reset_time_hack();
+    }
+
+    /// Returns the current timestamp considering any active time hack.
     ///
-    /// # Returns
-    /// Current local time taking hacks into account.
+    /// The time is read from the clock source and, when a time hack is
+    /// active, advanced from the hacked reference time by the time elapsed on
+    /// the source since the hack.
     #[must_use]
     pub fn current_time(&self) -> DateTime<Local> {
-        if let Some((base, start)) = self.time_hack {
-            let delta = Instant::now().saturating_duration_since(start);
-            let base_dt = Local::now().date_naive().and_time(base);
-            let base = match Local.from_local_datetime(&base_dt) {
-                LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt,
-                LocalResult::None => Local::now(),
-            };
-            if let Ok(d) = Duration::from_std(delta) {
-                base + d
-            } else {
-                base
-            }
-        } else {
-            Local::now()
-        }
+        let now = self.clock.now();
+        self.time_hack.map_or(now, |hack| hack.apply(now))
+    }
+
+    /// Returns the time the clock read at `instant`, considering any active
+    /// time hack.
+    fn time_at(&self, instant: Instant) -> DateTime<Local> {
+        let ago = Duration::from_std(instant.elapsed()).unwrap_or_default();
+        self.current_time() - ago
     }
 
     /// Returns the current time source name.
     ///
     /// # Returns
-    /// "Hacked" when a time hack is active, otherwise "System".
+    /// "Hacked" when a time hack is active, otherwise the name of the clock
+    /// source such as "System".
     #[must_use]
-    pub fn time_source(&self) -> &'static str {
+    pub fn time_source(&self) -> &str {
         if self.time_hack.is_some() {
             "Hacked"
         } else {
-            "System"
+            self.clock.name()
         }
     }
 
+    /// Reports whether the clock source is delivering time normally.
+    ///
+    /// A time hack does not change the health, since hacked time still
+    /// advances with the source.
+    #[must_use]
+    pub fn clock_health(&self) -> ClockHealth {
+        self.clock.health()
+    }
+
     /// Returns the currently selected entry index if any.
     ///
     /// # Returns
@@ -700,14 +1231,14 @@ impl App {
                     self.input = n.text.clone();
                     self.cursor = self.input.len();
                     self.note_time = Some(n.timestamp);
-                    self.edit_index = Some(idx);
+                    self.edit_id = Some(n.id);
                     self.mode = InputMode::EditingExistingNote;
                 }
                 Entry::Section(s) => {
                     self.input = s.title.clone();
                     self.cursor = self.input.len();
                     self.note_time = None;
-                    self.edit_index = Some(idx);
+                    self.edit_id = Some(s.id);
                     self.mode = InputMode::EditingExistingSection;
                 }
             }
@@ -722,10 +1253,137 @@ impl App {
         self.note_time = Some(self.current_time());
         self.input.clear();
         self.cursor = 0;
-        self.edit_index = None;
+        self.edit_id = None;
         self.mode = InputMode::EditingNote;
     }
 
+    /// Captures the current time as a pending note without opening the editor.
+    ///
+    /// The placeholder is appended to [`entries`](Self::entries) and can be
+    /// described later with [`describe_next_pending`](Self::describe_next_pending).
+    /// Any note currently being typed is left untouched.
+    pub fn mark_time(&mut self) {
+        let note = Note::pending(self.current_time());
+        self.apply_change(Change::Insert {
+            index: self.entries.len(),
+            entry: Entry::Note(note),
+        });
+        if !self.is_editing() {
+            self.selected = Some(self.entries.len() - 1);
+        }
+    }
+
+    /// Returns the number of captured timestamps that still need a description.
+    #[must_use]
+    pub fn pending_count(&self) -> usize {
+        self.notes.iter().filter(|n| n.pending).count()
+    }
+
+    /// Begin describing the oldest pending note if any.
+    ///
+    /// Finalizing the description with [`finalize_note`](Self::finalize_note)
+    /// moves on to the next pending note until the queue is empty.
+    pub fn describe_next_pending(&mut self) {
+        if let Some(idx) = self
+            .entries
+            .iter()
+            .position(|e| matches!(e, Entry::Note(n) if n.pending))
+        {
+            self.selected = Some(idx);
+            self.edit_selected();
+        }
+    }
+
+    fn is_editing(&self) -> bool {
+        matches!(
+            self.mode,
+            InputMode::EditingNote
+                | InputMode::EditingSection
+                | InputMode::EditingExistingNote
+                | InputMode::EditingExistingSection
+                | InputMode::EditingTimestamp
+        )
+    }
+
+    /// Begin correcting the timestamp of the selected note.
+    ///
+    /// The input is pre-filled with the current timestamp. Sections are
+    /// ignored since they carry no time.
+    ///
+    /// # See also
+    /// [`App::finalize_timestamp`]
+    pub fn edit_selected_time(&mut self) {
+        if let Some(idx) = self.selected {
+            if let Some(Entry::Note(n)) = self.entries.get(idx) {
+                self.input = self.display_zone.format(n.timestamp, "%H:%M:%S%.3f");
+                self.cursor = self.input.len();
+                self.note_time = Some(n.timestamp);
+                self.edit_id = Some(n.id);
+                self.mode = InputMode::EditingTimestamp;
+            }
+        }
+    }
+
+    /// Applies the timestamp typed in [`InputMode::EditingTimestamp`].
+    ///
+    /// The input is either an absolute time of day `HH:MM:SS[.mmm]` on the
+    /// note's date or a relative adjustment such as `-1.5s`, `+250ms`, `+2m`
+    /// or `-1h`; a bare number is taken as seconds. The first time a note is
+    /// moved its capture time is kept in [`Note::original_timestamp`]. Notes
+    /// that end up earlier than a preceding note are flagged by
+    /// [`App::is_out_of_order`] rather than moved.
+    ///
+    /// # Returns
+    /// `false` and stays in editing mode when the input cannot be parsed.
+    pub fn finalize_timestamp(&mut self) -> bool {
+        let Some(idx) = self.edit_id.and_then(|id| self.index_of(id)) else {
+            self.cancel_entry();
+            return false;
+        };
+        let Some(Entry::Note(n)) = self.entries.get(idx) else {
+            self.cancel_entry();
+            return false;
+        };
+        let Some(timestamp) = parse_timestamp_edit(&self.input, n.timestamp, self.display_zone)
+        else {
+            return false;
+        };
+        let mut note = n.clone();
+        note.retime(timestamp);
+        if note != *n {
+            self.apply_change(Change::Update {
+                index: idx,
+                entry: Entry::Note(note),
+            });
+        }
+        self.selected = Some(idx);
+        self.cancel_entry();
+        true
+    }
+
+    /// Returns the position of the entry with `id` in [`entries`](Self::entries).
+    #[must_use]
+    pub fn index_of(&self, id: Ulid) -> Option<usize> {
+        self.entries.iter().position(|e| e.id() == id)
+    }
+
+    /// Returns `true` if the note at `index` is timestamped earlier than the
+    /// nearest preceding note.
+    #[must_use]
+    pub fn is_out_of_order(&self, index: usize) -> bool {
+        let Some(Entry::Note(note)) = self.entries.get(index) else {
+            return false;
+        };
+        self.entries[..index]
+            .iter()
+            .rev()
+            .find_map(|e| match e {
+                Entry::Note(n) => Some(n.timestamp),
+                Entry::Section(_) => None,
+            })
+            .is_some_and(|prev| note.timestamp < prev)
+    }
+
     /// Starts a new section entry.
     ///
     /// Similar to [`start_note`], but prepares for a section title without a
@@ -734,23 +1392,91 @@ impl App {
         self.note_time = None;
         self.input.clear();
         self.cursor = 0;
-        self.edit_index = None;
+        self.edit_id = None;
+        self.insert_index = None;
         self.mode = InputMode::EditingSection;
     }
 
+    /// Starts a new section that is inserted above the selected entry.
+    ///
+    /// Behaves like [`start_section`](Self::start_section) when nothing is
+    /// selected.
+    pub fn start_section_above(&mut self) {
+        self.start_section();
+        self.insert_index = self.selected;
+    }
+
+    /// Starts a new section that is inserted below the selected entry.
+    ///
+    /// Behaves like [`start_section`](Self::start_section) when nothing is
+    /// selected.
+    pub fn start_section_below(&mut self) {
+        self.start_section();
+        self.insert_index = self.selected.map(|i| i + 1);
+    }
+
+    /// Asks for confirmation before deleting the selected entry.
+    ///
+    /// # See also
+    /// [`App::delete_selected`]
+    pub fn start_delete(&mut self) {
+        if self.selected.is_some() {
+            self.mode = InputMode::ConfirmDelete;
+        }
+    }
+
+    /// Deletes the selected entry without confirmation.
+    ///
+    /// The selection stays at the same position, or moves to the new last
+    /// entry when the last one was removed.
+    pub fn delete_selected(&mut self) {
+        if let Some(idx) = self.selected.filter(|i| *i < self.entries.len()) {
+            self.apply_change(Change::Remove { index: idx });
+            self.clamp_selection();
+        }
+        self.mode = InputMode::Normal;
+    }
+
+    /// Moves the selected entry one position up, keeping it selected.
+    pub fn move_selected_up(&mut self) {
+        if let Some(idx) = self.selected.filter(|i| *i > 0 && *i < self.entries.len()) {
+            self.apply_change(Change::Move {
+                from: idx,
+                to: idx - 1,
+            });
+            self.selected = Some(idx - 1);
+        }
+    }
+
+    /// Moves the selected entry one position down, keeping it selected.
+    pub fn move_selected_down(&mut self) {
+        if let Some(idx) = self.selected.filter(|i| i + 1 < self.entries.len()) {
+            self.apply_change(Change::Move {
+                from: idx,
+                to: idx + 1,
+            });
+            self.selected = Some(idx + 1);
+        }
+    }
+
     /// Begin entering a file path to save the current entries.
     ///
-    /// The default filename `notes.csv` is pre-filled.
+    /// The current file, or `notes.csv` in the save directory, is pre-filled.
     pub fn start_save(&mut self) {
-        self.input = self.save_dir.join("notes.csv").to_string_lossy().into();
+        self.input = self
+            .file_path
+            .clone()
+            .unwrap_or_else(|| self.save_dir.join("notes.csv"))
+            .to_string_lossy()
+            .into();
         self.cursor = self.input.len();
         self.mode = InputMode::Saving;
     }
 
     /// Begin entering a file path to load entries from.
     ///
-    /// Populates [`load_files`](Self::load_files) with available `.csv`
-    /// files in [`save_dir`](Self::save_dir).
+    /// Populates [`load_files`](Self::load_files) with the files in
+    /// [`save_dir`](Self::save_dir) whose extension matches a [`FileFormat`].
     pub fn start_load(&mut self) {
         self.load_files = fs::read_dir(&self.save_dir)
             .ok()
@@ -758,7 +1484,8 @@ impl App {
             .flatten()
             .filter_map(|e| {
                 let p = e.ok()?.path();
-                if p.extension().and_then(|s| s.to_str()) == Some("csv") {
+                let ext = p.extension().and_then(|s| s.to_str())?;
+                if FileFormat::from_extension(ext).is_some() {
                     Some(p)
                 } else {
                     None
@@ -799,6 +1526,91 @@ impl App {
         self.mode = InputMode::TimeHack;
     }
 
+    /// Open the session metadata overlay.
+    pub fn start_metadata(&mut self) {
+        self.metadata_selected = 0;
+        self.mode = InputMode::Metadata;
+    }
+
+    /// Returns the number of selectable rows in the metadata overlay.
+    ///
+    /// The rows are the title, the operator, each free-form field and a final
+    /// row for adding a new field.
+    #[must_use]
+    pub fn metadata_rows(&self) -> usize {
+        self.metadata.fields.len() + 3
+    }
+
+    /// Begin editing the selected metadata row.
+    ///
+    /// Free-form fields are edited as `key=value`.
+    pub fn edit_selected_metadata(&mut self) {
+        let fields = self.metadata.fields.len();
+        self.input = match self.metadata_selected {
+            0 => self.metadata.title.clone(),
+            1 => self.metadata.operator.clone(),
+            i if i < fields + 2 => {
+                let (k, v) = &self.metadata.fields[i - 2];
+                format!("{k}={v}")
+            }
+            _ => String::new(),
+        };
+        self.cursor = self.input.len();
+        self.mode = InputMode::EditingMetadata;
+    }
+
+    /// Stores the edited metadata value and returns to the metadata overlay.
+    ///
+    /// A free-form field whose value or whole input is left empty is removed.
+    /// Input for a free-form field without a `=` separator is ignored.
+    /// The log is only marked as changed if the metadata actually changed.
+    pub fn finalize_metadata(&mut self) {
+        let before = self.metadata.clone();
+        let input: String = self.input.drain(..).collect();
+        let fields = self.metadata.fields.len();
+        match self.metadata_selected {
+            0 => self.metadata.title = input.trim().to_string(),
+            1 => self.metadata.operator = input.trim().to_string(),
+            i => {
+                let existing = (i < fields + 2).then(|| i - 2);
+                if let Some((key, value)) = input.split_once('=') {
+                    let key = key.trim();
+                    if !key.is_empty() {
+                        if let Some(idx) = existing {
+                            if self.metadata.fields[idx].0 != key {
+                                self.metadata.fields.remove(idx);
+                            }
+                        }
+                        self.metadata.set_field(key, value.trim());
+                    }
+                } else if let (Some(idx), true) = (existing, input.trim().is_empty()) {
+                    self.metadata.fields.remove(idx);
+                }
+            }
+        }
+        self.dirty |= self.metadata != before;
+        self.metadata_selected = self.metadata_selected.min(self.metadata_rows() - 1);
+        self.cursor = 0;
+        self.mode = InputMode::Metadata;
+    }
+
+    /// Begin entering a file path to export the log in `format`.
+    ///
+    /// The current file name with the format's extension is pre-filled.
+    pub fn start_export(&mut self, format: ExportFormat) {
+        let base = self
+            .file_path
+            .clone()
+            .unwrap_or_else(|| self.save_dir.join("notes.csv"));
+        self.export_format = format;
+        self.input = base
+            .with_extension(format.extension())
+            .to_string_lossy()
+            .into();
+        self.cursor = self.input.len();
+        self.mode = InputMode::Exporting;
+    }
+
     /// Prompt for a new file name and create a blank file.
     pub fn start_new_file(&mut self) {
         self.input = self.save_dir.join("notes.csv").to_string_lossy().into();
@@ -815,31 +1627,44 @@ impl App {
 
     /// Finalizes the note if editing, pushing it into the note list.
     ///
-    /// If editing an existing note, its contents are replaced. When creating a
-    /// new note the timestamp captured by [`start_note`] is used.
+    /// If editing an existing note, its contents are replaced. Describing a
+    /// pending note with non-empty text clears its pending state and continues
+    /// with the next pending note. When creating a new note the timestamp
+    /// captured by [`start_note`] is used and the note is placed ahead of any
+    /// pending notes marked while it was being typed.
     pub fn finalize_note(&mut self) {
-        if let Some(idx) = self.edit_index.take() {
-            if let Entry::Note(ref mut n) = self.entries[idx] {
+        if let Some(idx) = self.edit_id.take().and_then(|id| self.index_of(id)) {
+            let mut described = false;
+            if let Some(Entry::Note(n)) = self.entries.get(idx) {
+                let mut n = n.clone();
                 n.text = self.input.drain(..).collect();
-                if let Some(orig) = self
-                    .notes
-                    .iter_mut()
-                    .find(|orig| orig.timestamp == n.timestamp)
-                {
-                    orig.text.clone_from(&n.text);
+                if n.pending && !n.text.is_empty() {
+                    n.pending = false;
+                    described = true;
                 }
+                self.apply_change(Change::Update {
+                    index: idx,
+                    entry: Entry::Note(n),
+                });
             }
             self.mode = InputMode::Normal;
             self.note_time = None;
             self.cursor = 0;
+            if described {
+                self.describe_next_pending();
+            }
         } else if let Some(time) = self.note_time.take() {
-            let note = Note {
-                timestamp: time,
-                text: self.input.drain(..).collect(),
-            };
-            self.notes.push(note.clone());
-            self.entries.push(Entry::Note(note));
-            self.selected = Some(self.entries.len() - 1);
+            let note = Note::new(time, self.input.drain(..).collect::<String>());
+            let pos = self
+                .entries
+                .iter()
+                .rposition(|e| !matches!(e, Entry::Note(n) if n.pending && n.timestamp > time))
+                .map_or(0, |p| p + 1);
+            self.apply_change(Change::Insert {
+                index: pos,
+                entry: Entry::Note(note),
+            });
+            self.selected = Some(pos);
             self.mode = InputMode::Normal;
             self.cursor = 0;
         }
@@ -850,693 +1675,3162 @@ impl App {
     /// Empty section titles are ignored when creating a new entry.
     pub fn finalize_section(&mut self) {
         let title = self.input.drain(..).collect::<String>();
-        if let Some(idx) = self.edit_index.take() {
-            if let Entry::Section(ref mut s) = self.entries[idx] {
-                s.title = title;
+        if let Some(idx) = self.edit_id.take().and_then(|id| self.index_of(id)) {
+            if let Some(Entry::Section(section)) = self.entries.get(idx) {
+                let section = Section {
+                    title,
+                    ..section.clone()
+                };
+                self.apply_change(Change::Update {
+                    index: idx,
+                    entry: Entry::Section(section),
+                });
             }
             self.note_time = None;
         } else if !title.is_empty() {
-            let section = Section { title };
-            self.entries.push(Entry::Section(section));
-            self.selected = Some(self.entries.len() - 1);
+            let section = Section::new(title);
+            let index = self
+                .insert_index
+                .take()
+                .map_or(self.entries.len(), |i| i.min(self.entries.len()));
+            self.apply_change(Change::Insert {
+                index,
+                entry: Entry::Section(section),
+            });
+            self.selected = Some(index);
         }
+        self.insert_index = None;
         self.mode = InputMode::Normal;
         self.cursor = 0;
     }
 
     fn finalize_time_hack(&mut self) {
         if self.input.is_empty() {
-            self.time_hack = None;
-        } else if let Ok(time) = NaiveTime::parse_from_str(&self.input, "%H:%M:%S%.f")
-            .or_else(|_| NaiveTime::parse_from_str(&self.input, "%H:%M:%S"))
-        {
-            let now = Local::now();
-            self.time_hack = Some((time, Instant::now()));
-            let hacked = self.current_time();
-            let note = Note {
-                timestamp: now,
-                text: format!(
-                    "Time Hack: {} -> {}",
-                    now.format("%H:%M:%S"),
-                    hacked.format("%H:%M:%S")
-                ),
-            };
-            self.notes.push(note.clone());
-            self.entries.push(Entry::Note(note));
-            self.selected = Some(self.entries.len() - 1);
+            self.reset_time_hack();
+        } else {
+            let now = self.clock.now();
+            match self.input.parse::<HackTime>() {
+                Ok(time) => match time.resolve_in(now, self.display_zone) {
+                    Some(reference) => {
+                        self.apply_time_hack(reference, now, None);
+                        self.selected = Some(self.entries.len() - 1);
+                    }
+                    None => self.set_status(
+                        StatusKind::Error,
+                        format!(
+                            "{} does not exist in the {} time zone",
+                            self.input, self.display_zone
+                        ),
+                    ),
+                },
+                Err(e) => self.set_status(StatusKind::Error, e),
+            }
         }
         self.input.clear();
         self.cursor = 0;
         self.mode = InputMode::Normal;
     }
 
-    /// Cancels the current entry editing.
-    pub fn cancel_entry(&mut self) {
-        self.input.clear();
-        self.note_time = None;
-        self.edit_index = None;
-        self.cursor = 0;
-        self.mode = InputMode::Normal;
+    /// Sets the clock so that it read `reference` when the clock source read
+    /// `now` and logs the change as a note.
+    fn apply_time_hack(
+        &mut self,
+        reference: DateTime<Local>,
+        now: DateTime<Local>,
+        source: Option<String>,
+    ) {
+        let hack = TimeHack::new(reference, now);
+        let previous = self.time_hack.replace(hack);
+        // The date is only shown when the hack moves the clock to another day.
+        let zone = self.display_zone;
+        let format = if zone.wall_clock(reference).date() == zone.wall_clock(now).date() {
+            "%H:%M:%S"
+        } else {
+            "%Y-%m-%d %H:%M:%S"
+        };
+        let mut note = Note::new(
+            now,
+            format!(
+                "Time Hack: {} -> {}",
+                zone.format(now, format),
+                zone.format(reference, format)
+            ),
+        );
+        note.source = source;
+        note.time_hack = Some(hack);
+        self.apply_step(
+            vec![Change::Insert {
+                index: self.entries.len(),
+                entry: Entry::Note(note),
+            }],
+            Some(previous),
+        );
+    }
+
+    /// Returns the time hacks recorded in the log, in the order they were taken.
+    #[must_use]
+    pub fn time_hacks(&self) -> Vec<TimeHack> {
+        let mut hacks: Vec<TimeHack> = self.notes.iter().filter_map(|n| n.time_hack).collect();
+        hacks.sort_by_key(|h| h.set_at);
+        hacks
+    }
+
+    /// Estimates the drift of the clock source against the time hack
+    /// reference, see [`clock_drift`].
+    #[must_use]
+    pub fn clock_drift(&self) -> Option<f64> {
+        clock_drift(&self.time_hacks())
     }
 
-    /// Saves all entries to a CSV file.
+    /// Re-timestamps notes taken between consecutive time hacks by
+    /// interpolating the offset between them, as one undoable step.
     ///
-    /// # Arguments
-    /// * `path` - Destination file path.
+    /// Only notes that follow a hack in the log and precede the next one are
+    /// moved. Notes taken after the clock source was reset were not stamped
+    /// with the hack and are left alone, as are notes whose timestamp was
+    /// already corrected, by hand or by an earlier call, and the notes
+    /// recording the hacks and resets.
     ///
-    /// # Errors
-    /// Returns any I/O or serialization errors encountered.
-    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
-        let mut wtr = csv::WriterBuilder::new()
-            .has_headers(false)
-            .from_path(path)?;
-        for e in &self.entries {
-            match e {
-                Entry::Note(n) => {
-                    wtr.serialize(("note", n.timestamp.to_rfc3339(), &n.text))?;
-                }
-                Entry::Section(s) => {
-                    wtr.serialize(("section", "", &s.title))?;
+    /// # Returns
+    /// The number of notes that were moved.
+    pub fn interpolate_time_hacks(&mut self) -> usize {
+        let mut changes = Vec::new();
+        // The last hack, whether it is still in effect and the notes stamped
+        // with it.
+        let mut from = None;
+        let mut hacked = false;
+        let mut stamped: Vec<(usize, &Note)> = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let Entry::Note(n) = entry else { continue };
+            if let Some(to) = n.time_hack {
+                if let Some(from) = from {
+                    changes.extend(stamped.drain(..).filter_map(|(index, n)| {
+                        let mut note = n.clone();
+                        note.retime(interpolate_hacks(&[from, to], n.timestamp)?);
+                        (note != *n).then_some(Change::Update {
+                            index,
+                            entry: Entry::Note(note),
+                        })
+                    }));
                 }
+                from = Some(to);
+                hacked = true;
+            } else if n.hack_reset {
+                hacked = false;
+            } else if hacked && n.original_timestamp.is_none() {
+                stamped.push((index, n));
             }
         }
-        wtr.flush()?;
-        Ok(())
+        let moved = changes.len();
+        if moved > 0 {
+            self.apply_step(changes, None);
+        }
+        moved
     }
 
-    /// Loads entries from a CSV file, replacing existing ones.
+    /// Accepts commands from other programs on a Unix domain socket at `path`.
     ///
-    /// # Arguments
-    /// * `path` - File path to load from.
+    /// Each connection sends one JSON [`RemoteCommand`] per line. Commands are
+    /// queued as they arrive and applied by [`process_remote`](Self::process_remote),
+    /// which the UI loop calls continuously. The socket file is removed when
+    /// the `App` is dropped.
     ///
     /// # Errors
-    /// Returns any I/O or deserialization errors encountered.
-    pub fn load_from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(path)?;
-        let mut app = Self::default();
-        for result in rdr.records() {
-            let record = result?;
-            match record.get(0) {
-                Some("note") => {
-                    if let (Some(ts), Some(text)) = (record.get(1), record.get(2)) {
-                        let ts = DateTime::parse_from_rfc3339(ts)
-                            .map_err(io::Error::other)?
-                            .with_timezone(&Local);
-                        let note = Note {
-                            timestamp: ts,
-                            text: text.to_string(),
-                        };
-                        app.notes.push(note.clone());
-                        app.entries.push(Entry::Note(note));
-                    }
-                }
-                Some("section") => {
-                    if let Some(title) = record.get(2) {
-                        app.entries.push(Entry::Section(Section {
-                            title: title.to_string(),
-                        }));
-                    }
-                }
-                _ => {}
-            }
-        }
-        Ok(app)
+    /// Returns [`AppError::NotFound`] or [`AppError::PermissionDenied`] if the
+    /// socket cannot be created, or [`AppError::Io`] if another process is
+    /// already listening on `path`.
+    #[cfg(unix)]
+    pub fn listen_socket<P: AsRef<Path>>(&mut self, path: P) -> Result<(), AppError> {
+        let path = path.as_ref();
+        self.remote
+            .listen_socket(path)
+            .map_err(|e| AppError::from_io(e, path))
     }
 
-    /// Loads entries from a file, replacing the current state.
+    /// Turns UDP datagrams received on `addr` into timestamp markers.
     ///
-    /// # Arguments
-    /// * `path` - File path to load from.
+    /// An empty datagram marks the current time with
+    /// [`current_time`](Self::current_time) as of its arrival, text adds a
+    /// note with that text, and a JSON object is read as a [`RemoteCommand`]
+    /// if it is a note or mark.
+    ///
+    /// # Returns
+    /// The bound address, which tells the port chosen when binding port 0.
     ///
     /// # Errors
-    /// Returns any I/O or deserialization errors encountered.
-    pub fn load_from_file_in_place<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
-        let new_app = Self::load_from_file(path)?;
-        *self = new_app;
+    /// Returns [`AppError::Io`] if the address cannot be bound.
+    pub fn listen_udp(&mut self, addr: impl ToSocketAddrs) -> Result<SocketAddr, AppError> {
+        Ok(self.remote.listen_udp(addr)?)
+    }
+
+    /// Streams every change to the log to `path` as JSON Lines while the
+    /// session runs.
+    ///
+    /// The stream starts with a `reset` event listing the current entries and
+    /// their IDs, followed by `insert`, `update`, `remove` and `move` events
+    /// that refer to entries by ID, and `time_hack` events when the clock is
+    /// set. `path` may be a regular file, which is appended to, a FIFO, which
+    /// is written once a reader opens it, or a descriptor such as `/dev/fd/3`.
+    ///
+    /// # Errors
+    /// Returns [`AppError::NotFound`], [`AppError::PermissionDenied`] or
+    /// [`AppError::Io`] if a regular file cannot be opened for writing.
+    pub fn stream_to<P: AsRef<Path>>(&mut self, path: P) -> Result<(), AppError> {
+        let path = path.as_ref();
+        let stream =
+            EventStream::open(path, &self.entries).map_err(|e| AppError::from_io(e, path))?;
+        self.stream = Some(stream);
         Ok(())
     }
 
-    /// Processes a key event in normal mode.
-    fn handle_normal_key(&mut self, key: KeyEvent) {
-        match key.code {
-            c if c == self.keys.up => self.select_previous(),
-            c if c == self.keys.down => self.select_next(),
-            c if c == self.keys.edit => self.edit_selected(),
-            c if c == self.keys.new_note => self.start_note(),
-            c if c == self.keys.new_section => self.start_section(),
-            c if c == self.keys.save => self.start_save(),
-            c if c == self.keys.load => self.start_load(),
-            c if c == self.keys.file_menu => self.start_file_menu(),
-            c if c == self.keys.bindings => self.start_keybindings(),
-            c if c == self.keys.theme => self.start_theme_menu(),
-            c if c == self.keys.time_hack => self.start_time_hack(),
-            _ => {}
+    /// Writes the current time hack to the event stream, if one is open.
+    fn stream_time_hack(&self) {
+        if let Some(stream) = &self.stream {
+            stream.time_hack(self.time_hack.map(|hack| (hack.reference(), hack.set_at)));
         }
     }
 
-    /// Processes a key event in any editing mode.
-    fn handle_editing_key(&mut self, key: KeyEvent) {
-        match key.code {
-            c if c == self.keys.new_note => match self.mode {
-                InputMode::EditingNote | InputMode::EditingExistingNote => {
-                    self.finalize_note();
-                }
-                InputMode::EditingSection | InputMode::EditingExistingSection => {
-                    self.finalize_section();
-                }
-                InputMode::Saving => {
-                    let path: String = self.input.drain(..).collect();
-                    if !path.is_empty() {
-                        self.save_to_file(path).ok();
-                    }
-                    self.cursor = 0;
-                    self.mode = InputMode::Normal;
-                }
-                InputMode::NewFile => {
-                    let path: String = self.input.drain(..).collect();
-                    if !path.is_empty() {
-                        self.entries.clear();
-                        self.notes.clear();
-                        self.save_to_file(&path).ok();
-                    }
-                    self.cursor = 0;
-                    self.mode = InputMode::Normal;
-                }
-                InputMode::TimeHack => {
-                    self.finalize_time_hack();
-                }
-                InputMode::Normal
-                | InputMode::Loading
-                | InputMode::KeyBindings
-                | InputMode::KeyCapture
-                | InputMode::ConfirmReplace
-                | InputMode::ThemeSelect
-                | InputMode::BindWarning
-                | InputMode::FileMenu => {}
-            },
-            c if c == self.keys.cancel => self.cancel_entry(),
-            KeyCode::Char('r') if matches!(self.mode, InputMode::TimeHack) => {
-                self.time_hack = None;
-                self.input.clear();
-                self.cursor = 0;
-                self.mode = InputMode::Normal;
-            }
-            KeyCode::Char(c)
-                if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
-            {
-                if self.cursor >= self.input.len() {
-                    self.input.push(c);
-                } else {
-                    self.input.insert(self.cursor, c);
-                }
-                self.cursor += 1;
-            }
-            KeyCode::Backspace => {
-                if self.cursor > 0 && self.cursor <= self.input.len() {
-                    self.cursor -= 1;
-                    self.input.remove(self.cursor);
-                }
-            }
-            KeyCode::Left => {
-                if self.cursor > 0 {
-                    self.cursor -= 1;
-                }
-            }
-            KeyCode::Right => {
-                if self.cursor < self.input.len() {
-                    self.cursor += 1;
-                }
-            }
-            _ => {}
+    /// Applies every command received from other programs since the last call.
+    ///
+    /// # Returns
+    /// `true` if any command was applied.
+    pub fn process_remote(&mut self) -> bool {
+        let mut applied = false;
+        while let Some(event) = self.remote.try_next() {
+            self.apply_remote(event);
+            applied = true;
         }
+        applied
     }
 
-    fn handle_loading_key(&mut self, key: KeyEvent) {
-        match key.code {
-            c if c == self.keys.up => {
-                if self.load_selected > 0 {
-                    self.load_selected -= 1;
-                }
+    /// Applies a command from another program as if the operator had entered it.
+    ///
+    /// New entries are appended to the log with the sender recorded as their
+    /// source, and are stamped with the time the command arrived. Anything the
+    /// operator is typing or selecting is left alone.
+    pub fn apply_remote(&mut self, event: RemoteEvent) {
+        let arrived = self.time_at(event.received);
+        let source = Some(event.source);
+        let entry = match event.command {
+            RemoteCommand::Note { text, at } => {
+                let mut note = Note::new(at.unwrap_or(arrived), text);
+                note.source = source;
+                Entry::Note(note)
             }
-            c if c == self.keys.down => {
-                if self.load_selected + 1 < self.load_files.len() {
-                    self.load_selected += 1;
-                }
+            RemoteCommand::Mark { at } => {
+                let mut note = Note::pending(at.unwrap_or(arrived));
+                note.source = source;
+                Entry::Note(note)
             }
-            c if c == self.keys.new_note => {
-                if let Some(path) = self.load_files.get(self.load_selected).cloned() {
-                    self.load_from_file_in_place(path).ok();
+            RemoteCommand::Section { title } => Entry::Section(Section {
+                source,
+                ..Section::new(title)
+            }),
+            RemoteCommand::TimeHack { time } => {
+                let ago = Duration::from_std(event.received.elapsed()).unwrap_or_default();
+                let now = self.clock.now() - ago;
+                match time.resolve_in(now, self.display_zone) {
+                    Some(reference) => self.apply_time_hack(reference, now, source),
+                    None => log::warn!(
+                        "Ignoring time hack to {time}, which does not exist in the {} time zone",
+                        self.display_zone
+                    ),
                 }
-                self.mode = InputMode::Normal;
+                return;
             }
-            c if c == self.keys.cancel => {
-                self.mode = InputMode::Normal;
+        };
+        self.apply_change(Change::Insert {
+            index: self.entries.len(),
+            entry,
+        });
+    }
+
+    /// Clears any active time hack so the system clock is used again and logs
+    /// the change as a note.
+    fn reset_time_hack(&mut self) {
+        if let Some(hack) = self.time_hack {
+            let now = self.clock.now();
+            let zone = self.display_zone;
+            let mut note = Note::new(
+                now,
+                format!(
+                    "Time Hack Reset: {} -> {}",
+                    zone.format(hack.apply(now), "%H:%M:%S"),
+                    zone.format(now, "%H:%M:%S")
+                ),
+            );
+            note.hack_reset = true;
+            let previous = self.time_hack.take();
+            self.apply_step(
+                vec![Change::Insert {
+                    index: self.entries.len(),
+                    entry: Entry::Note(note),
+                }],
+                Some(previous),
+            );
+        }
+    }
+
+    /// Cancels the current entry editing.
+    pub fn cancel_entry(&mut self) {
+        if self.quit == QuitState::AfterSave {
+            self.quit = QuitState::Running;
+        }
+        self.input.clear();
+        self.note_time = None;
+        self.edit_id = None;
+        self.insert_index = None;
+        self.cursor = 0;
+        self.mode = InputMode::Normal;
+    }
+
+    /// Saves the session metadata and all entries to a file.
+    ///
+    /// The format is chosen from the extension with [`FileFormat::from_path`].
+    /// CSV files are written in the current versioned layout: a
+    /// `#rtnt,<version>` marker row, a header row naming the columns, `meta`
+    /// and `field` rows holding the [`SessionMetadata`], then one row per entry.
+    ///
+    /// # Arguments
+    /// * `path` - Destination file path.
+    ///
+    /// # Errors
+    /// Returns [`AppError::NotFound`] if the directory does not exist,
+    /// [`AppError::PermissionDenied`] if it may not be written, or
+    /// [`AppError::Io`] for any other I/O or serialization error.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), AppError> {
+        let path = path.as_ref();
+        self.save_as(path, FileFormat::from_path(path))
+    }
+
+    /// Saves the session to `path` in `format`, whatever its extension.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`App::save_to_file`].
+    ///
+    /// The file is locked while it is written, waiting for any other `rtnt`
+    /// process that is writing it.
+    pub fn save_as<P: AsRef<Path>>(&self, path: P, format: FileFormat) -> Result<(), AppError> {
+        let path = path.as_ref();
+        LockedFile::open(path)
+            .and_then(|mut file| file.replace(|out| self.write_session(out, format)))
+            .map_err(|e| AppError::from_io(e, path))
+    }
+
+    /// Adds `entry` to the end of the log at `path` without loading it into an
+    /// `App`, creating the file if it does not exist.
+    ///
+    /// The file is locked for the duration, so this is safe while an
+    /// interactive session has the same file open: that session merges the
+    /// new entry the next time it saves. CSV and JSON Lines files are
+    /// appended to; JSON documents and CSV files in an older layout are
+    /// rewritten.
+    ///
+    /// # Example
+    /// ```
+    /// use real_time_note_taker::{App, Entry, Section};
+    ///
+    /// let dir = std::env::temp_dir().join("rtnt-append-doc");
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let path = dir.join("log.csv");
+    /// # let _ = std::fs::remove_file(&path);
+    /// App::append_to_file(&path, Entry::Section(Section::new("Taxi"))).unwrap();
+    /// assert_eq!(App::load_from_file(&path).unwrap().entries.len(), 1);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the errors of [`App::load_from_file`] if the existing file
+    /// cannot be read, so a damaged file is never extended, or those of
+    /// [`App::save_to_file`] if it cannot be written.
+    pub fn append_to_file<P: AsRef<Path>>(path: P, entry: Entry) -> Result<(), AppError> {
+        let path = path.as_ref();
+        let format = FileFormat::from_path(path);
+        let mut file = LockedFile::open(path).map_err(|e| AppError::from_io(e, path))?;
+        let is_new = file.is_empty().map_err(|e| AppError::from_io(e, path))?;
+        let result = if is_new {
+            let app = Self {
+                entries: vec![entry],
+                ..Self::default()
+            };
+            file.replace(|out| app.write_session(out, format))
+        } else {
+            let existing = format.read(path)?;
+            if format.can_append(path)? {
+                file.append(|out| format.append(out, &entry))
+            } else {
+                let mut entries = existing.entries;
+                entries.push(entry);
+                let time_source = existing.metadata.time_source.clone();
+                file.replace(|out| {
+                    format.write(
+                        out,
+                        &existing.metadata,
+                        time_source.as_deref().unwrap_or("System"),
+                        &entries,
+                    )
+                })
             }
-            _ => {}
+        };
+        result.map_err(|e| AppError::from_io(e, path))
+    }
+
+    /// Writes the session to `out` in `format`, for example to standard output.
+    ///
+    /// A log loaded from a file keeps the time source it was saved with.
+    ///
+    /// # Example
+    /// ```
+    /// use real_time_note_taker::{App, FileFormat};
+    ///
+    /// let mut out = Vec::new();
+    /// App::new().write_session(&mut out, FileFormat::Csv).unwrap();
+    /// assert!(out.starts_with(b"#rtnt,"));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns any I/O or serialization error.
+    pub fn write_session(&self, out: impl io::Write, format: FileFormat) -> io::Result<()> {
+        format.write(
+            out,
+            &self.metadata,
+            self.recorded_time_source(),
+            &self.entries,
+        )
+    }
+
+    /// Renders the session metadata and entries as a document in `format`.
+    ///
+    /// A log loaded from a file is shown with the time source it was saved
+    /// with.
+    ///
+    /// # Example
+    /// ```
+    /// use real_time_note_taker::{App, ExportFormat};
+    ///
+    /// let app = App::new();
+    /// assert!(app.export(ExportFormat::Markdown).starts_with("# "));
+    /// ```
+    #[must_use]
+    pub fn export(&self, format: ExportFormat) -> String {
+        format.render(
+            &self.metadata,
+            self.recorded_time_source(),
+            &self.entries,
+            self.display_zone,
+            &self.theme(),
+        )
+    }
+
+    /// Returns the time source the log was saved with, or the current one if
+    /// it has not been saved.
+    fn recorded_time_source(&self) -> &str {
+        self.metadata
+            .time_source
+            .as_deref()
+            .unwrap_or(self.time_source())
+    }
+
+    /// Writes the log to `path` as a document in `format`.
+    ///
+    /// # Arguments
+    /// * `path` - Destination file path.
+    /// * `format` - Document format to write.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`App::save_to_file`].
+    ///
+    /// # See also
+    /// [`App::export`]
+    pub fn export_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: ExportFormat,
+    ) -> Result<(), AppError> {
+        let path = path.as_ref();
+        fs::write(path, self.export(format)).map_err(|e| AppError::from_io(e, path))
+    }
+
+    /// Loads session metadata and entries from a file, replacing existing ones.
+    ///
+    /// The format is chosen from the extension with [`FileFormat::from_path`].
+    /// CSV files written before the format was versioned are migrated on load
+    /// and start with default metadata if they have none.
+    ///
+    /// # Arguments
+    /// * `path` - File path to load from.
+    ///
+    /// # Errors
+    /// Returns [`AppError::NotFound`] or [`AppError::PermissionDenied`] if the
+    /// file cannot be opened, [`AppError::Parse`] with the offending line if a
+    /// record is malformed, [`AppError::UnsupportedVersion`] if the file was
+    /// written by a newer release, or [`AppError::Io`] for other I/O errors.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, AppError> {
+        let path = path.as_ref();
+        let file = FileFormat::from_path(path).read(path)?;
+        let mut app = Self {
+            saved_ids: file.entries.iter().map(Entry::id).collect(),
+            entries: file.entries,
+            metadata: file.metadata,
+            file_path: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        app.sync_notes();
+        Ok(app)
+    }
+
+    /// Loads entries and session metadata from a file, replacing the current ones.
+    ///
+    /// Settings such as key bindings, theme and save directory are kept.
+    ///
+    /// # Arguments
+    /// * `path` - File path to load from.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`App::load_from_file`].
+    pub fn load_from_file_in_place<P: AsRef<Path>>(&mut self, path: P) -> Result<(), AppError> {
+        let loaded = Self::load_from_file(&path)?;
+        self.switch_session(
+            loaded.entries,
+            SessionState {
+                file_path: Some(path.as_ref().to_path_buf()),
+                saved_ids: loaded.saved_ids,
+                metadata: loaded.metadata,
+                dirty: false,
+            },
+        );
+        self.selected = None;
+        self.edit_id = None;
+        self.note_time = None;
+        Ok(())
+    }
+
+    /// Returns the message currently shown in the status line, if any.
+    ///
+    /// Informational messages disappear after a few seconds; errors remain
+    /// until the next key press.
+    #[must_use]
+    pub fn status(&self) -> Option<&StatusMessage> {
+        self.status.as_ref().filter(|s| !s.is_expired())
+    }
+
+    /// Shows a message in the status line, replacing any previous one.
+    pub fn set_status(&mut self, kind: StatusKind, text: impl Into<String>) {
+        let message = StatusMessage::new(kind, text);
+        if kind == StatusKind::Error {
+            log::error!("{}", message.text);
         }
+        self.status = Some(message);
     }
 
-    fn handle_keybindings_key(&mut self, key: KeyEvent) {
-        match key.code {
-            c if c == self.keys.up => {
-                if self.keybind_selected > 0 {
-                    self.keybind_selected -= 1;
-                }
+    /// Removes the current status message.
+    pub fn clear_status(&mut self) {
+        self.status = None;
+    }
+
+    /// Saves to `path` and reports the outcome in the status line.
+    ///
+    /// A successful save makes `path` the current file and clears the unsaved
+    /// changes flag.
+    ///
+    /// Entries other programs added to the current file since it was last
+    /// loaded or saved are merged in first, matched by ID, so they are not
+    /// overwritten.
+    fn save_with_status(&mut self, path: &Path) -> bool {
+        match self.merge_and_save(path) {
+            Ok(merged) => {
+                let merged = match merged {
+                    0 => String::new(),
+                    n => format!(", including {n} added by another program"),
+                };
+                self.set_status(
+                    StatusKind::Info,
+                    format!(
+                        "Saved {} entries to {}{merged}",
+                        self.entries.len(),
+                        path.display()
+                    ),
+                );
+                self.dirty = false;
+                self.file_path = Some(path.to_path_buf());
+                true
             }
-            c if c == self.keys.down => {
-                if self.keybind_selected + 1 < Action::ALL.len() {
-                    self.keybind_selected += 1;
-                }
+            Err(e) => {
+                self.set_status(StatusKind::Error, format!("Save failed: {e}"));
+                false
             }
-            KeyCode::Enter => self.start_capture_binding(),
-            c if c == self.keys.cancel => self.mode = InputMode::Normal,
-            _ => {}
         }
     }
 
-    fn handle_capture_key(&mut self, key: KeyEvent) {
-        if let Some(action) = self.capture_action {
-            match key.code {
-                c if c == self.keys.cancel => {
-                    self.capture_action = None;
-                    self.mode = InputMode::KeyBindings;
-                }
-                code if code == self.keys.bindings && action != Action::Bindings => {
-                    self.mode = InputMode::BindWarning;
-                }
-                code => {
-                    self.capture_action = None;
-                    if let Some(conflict) = self.keys.action_for_key(code) {
-                        if conflict != action {
-                            self.pending_key = Some(code);
-                            self.pending_action = Some(action);
-                            self.pending_conflict = Some(conflict);
-                            self.mode = InputMode::ConfirmReplace;
-                            return;
-                        }
-                    }
-                    self.keys.set(action, code);
-                    self.keys.save();
-                    self.mode = InputMode::KeyBindings;
+    /// Locks `path`, merges entries other programs added to it and saves.
+    ///
+    /// # Returns
+    /// The number of entries merged.
+    fn merge_and_save(&mut self, path: &Path) -> Result<usize, AppError> {
+        let format = FileFormat::from_path(path);
+        let mut file = LockedFile::open(path).map_err(|e| AppError::from_io(e, path))?;
+        let mut merged = 0;
+        if self.file_path.as_deref() == Some(path) {
+            // A file that cannot be read has nothing worth merging and is
+            // replaced, as it would have been before.
+            if let Ok(on_disk) = format.read(path) {
+                // Entries this session has seen and since deleted stay deleted.
+                let known: HashSet<Ulid> = self.entries.iter().map(Entry::id).collect();
+                let changes: Vec<_> = on_disk
+                    .entries
+                    .into_iter()
+                    .filter(|e| !self.saved_ids.contains(&e.id()) && !known.contains(&e.id()))
+                    .enumerate()
+                    .map(|(i, entry)| Change::Insert {
+                        index: self.entries.len() + i,
+                        entry,
+                    })
+                    .collect();
+                merged = changes.len();
+                if merged > 0 {
+                    self.apply_step(changes, None);
                 }
             }
         }
+        self.metadata.time_source = Some(self.time_source().to_owned());
+        file.replace(|out| self.write_session(out, format))
+            .map_err(|e| AppError::from_io(e, path))?;
+        self.saved_ids = self.entries.iter().map(Entry::id).collect();
+        Ok(merged)
     }
 
-    fn handle_bind_warning_key(&mut self, key: KeyEvent) {
-        if key.code == self.keys.cancel {
-            self.capture_action = None;
-            self.mode = InputMode::KeyBindings;
-        } else {
-            self.mode = InputMode::KeyCapture;
+    /// Exports to `path` and reports the outcome in the status line.
+    fn export_with_status(&mut self, path: &Path) {
+        let format = self.export_format;
+        match self.export_to_file(path, format) {
+            Ok(()) => self.set_status(
+                StatusKind::Info,
+                format!("Exported {} to {}", format.display_name(), path.display()),
+            ),
+            Err(e) => self.set_status(StatusKind::Error, format!("Export failed: {e}")),
         }
     }
 
-    fn handle_confirm_key(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Enter => {
-                if let (Some(new_key), Some(new_action), Some(conflict)) = (
-                    self.pending_key.take(),
-                    self.pending_action.take(),
-                    self.pending_conflict.take(),
-                ) {
-                    self.keys.set(conflict, KeyCode::Null);
-                    self.keys.set(new_action, new_key);
-                    self.keys.save();
+    /// Returns `true` if entries or metadata changed since the last save or load.
+    #[must_use]
+    pub const fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Returns the file most recently saved to or loaded from.
+    #[must_use]
+    pub fn file_path(&self) -> Option<&Path> {
+        self.file_path.as_deref()
+    }
+
+    /// Saves to `path` automatically when the user quits instead of asking.
+    ///
+    /// Used by `--file` so the session is written back to the file it was
+    /// loaded from.
+    pub fn save_on_quit(&mut self, path: impl Into<PathBuf>) {
+        self.file_path = Some(path.into());
+        self.save_on_quit = true;
+    }
+
+    /// Returns `true` once the application should exit.
+    #[must_use]
+    pub const fn should_quit(&self) -> bool {
+        matches!(self.quit, QuitState::Done)
+    }
+
+    /// Quits, first asking what to do with unsaved changes.
+    ///
+    /// A new note being typed is kept: its text is saved as a note, or the
+    /// captured time is kept as a pending mark if nothing was typed yet. An
+    /// edit of an existing note is applied, while other prompts are cancelled.
+    /// Unsaved changes then open [`InputMode::ConfirmQuit`], unless
+    /// [`save_on_quit`](Self::save_on_quit) was requested, in which case the
+    /// file is saved and the prompt only appears if saving fails.
+    pub fn request_quit(&mut self) {
+        match self.mode {
+            InputMode::EditingNote if self.input.trim().is_empty() => {
+                if let Some(time) = self.note_time.take() {
+                    let index = self.entries.len();
+                    self.apply_change(Change::Insert {
+                        index,
+                        entry: Entry::Note(Note::pending(time)),
+                    });
                 }
-                self.mode = InputMode::KeyBindings;
-            }
-            c if c == self.keys.cancel => {
-                self.pending_key = None;
-                self.pending_action = None;
-                self.pending_conflict = None;
-                self.mode = InputMode::KeyBindings;
             }
+            InputMode::EditingNote | InputMode::EditingExistingNote => self.finalize_note(),
+            InputMode::EditingExistingSection => self.finalize_section(),
             _ => {}
         }
-    }
-
-    fn handle_theme_key(&mut self, key: KeyEvent) {
-        match key.code {
-            c if c == self.keys.up => {
-                if self.theme_selected > 0 {
-                    self.theme_selected -= 1;
+        self.cancel_entry();
+        if self.save_on_quit {
+            if let Some(path) = self.file_path.clone() {
+                if self.save_with_status(&path) {
+                    self.quit = QuitState::Done;
+                    return;
                 }
             }
-            c if c == self.keys.down => {
-                if self.theme_selected + 1 < ThemeName::ALL.len() {
-                    self.theme_selected += 1;
-                }
+        }
+        if self.dirty {
+            self.quit_selected = 0;
+            self.mode = InputMode::ConfirmQuit;
+        } else {
+            self.quit = QuitState::Done;
+        }
+    }
+
+    /// Saves to the current file and quits, or prompts for a file name first.
+    fn save_and_quit(&mut self) {
+        if let Some(path) = self.file_path.clone() {
+            if self.save_with_status(&path) {
+                self.quit = QuitState::Done;
             }
-            KeyCode::Enter => {
-                if let Some(t) = ThemeName::ALL.get(self.theme_selected).copied() {
-                    self.theme = t;
-                    t.save();
+            self.mode = InputMode::Normal;
+        } else {
+            self.start_save();
+            self.quit = QuitState::AfterSave;
+        }
+    }
+
+    /// Returns the location of this session's crash recovery journal.
+    ///
+    /// # Returns
+    /// A file named after the log and a unique ID inside
+    /// [`save_dir`](Self::save_dir), or `None` before
+    /// [`open_journal`](Self::open_journal).
+    #[must_use]
+    pub fn journal_path(&self) -> Option<&Path> {
+        self.journal.as_ref().map(Journal::path)
+    }
+
+    /// Starts journaling every entry mutation to [`journal_path`](Self::journal_path).
+    ///
+    /// Each finalized note, section, edit and time hack is appended and synced
+    /// to disk so a crashed session can be recovered. When a journal for the
+    /// same log was left behind by an unclean shutdown and still contains
+    /// entries, they are kept aside and the application switches to
+    /// [`InputMode::RecoverSession`] so the user can restore or discard them.
+    /// Journals of sessions that are still running are never offered.
+    ///
+    /// # Errors
+    /// Returns any I/O error raised while reading or creating the journal.
+    ///
+    /// # See also
+    /// [`App::restore_recovered`], [`App::discard_recovered`], [`App::close_journal`]
+    pub fn open_journal(&mut self) -> io::Result<()> {
+        let stem = self
+            .file_path
+            .as_deref()
+            .and_then(Path::file_stem)
+            .and_then(|s| s.to_str())
+            .unwrap_or("session")
+            .to_string();
+        while let Some(orphan) = Orphan::find(&self.save_dir, &stem)? {
+            let entries = orphan.replay()?;
+            if entries.is_empty() {
+                orphan.remove()?;
+                continue;
+            }
+            self.recovered = Some(entries);
+            self.orphan = Some(orphan);
+            self.mode = InputMode::RecoverSession;
+            break;
+        }
+        self.journal = Some(Journal::start(&self.save_dir, &stem, &self.entries)?);
+        Ok(())
+    }
+
+    /// Returns entries recovered from an unclean journal that await a decision.
+    #[must_use]
+    pub fn recovered_entries(&self) -> Option<&[Entry]> {
+        self.recovered.as_deref()
+    }
+
+    /// Replaces the current entries with the recovered ones as an undoable
+    /// change that still needs saving.
+    ///
+    /// The time hack in effect at the end of the recovered log is restored.
+    pub fn restore_recovered(&mut self) {
+        if let Some(entries) = self.recovered.take() {
+            // The session went on with the clock it had when it ended.
+            let hack = last_time_hack(&entries);
+            let previous = std::mem::replace(&mut self.time_hack, hack);
+            self.apply_step(
+                vec![Change::Reset { entries }],
+                (previous != hack).then_some(previous),
+            );
+            self.selected = self.entries.len().checked_sub(1);
+        }
+        self.remove_orphan();
+        self.mode = InputMode::Normal;
+    }
+
+    /// Drops the recovered entries and deletes the journal they came from.
+    pub fn discard_recovered(&mut self) {
+        self.recovered = None;
+        self.remove_orphan();
+        self.mode = InputMode::Normal;
+    }
+
+    fn remove_orphan(&mut self) {
+        if let Some(Err(e)) = self.orphan.take().map(Orphan::remove) {
+            self.set_status(
+                StatusKind::Error,
+                format!("Failed to remove recovered journal: {e}"),
+            );
+        }
+    }
+
+    /// Stops journaling and deletes the journal after a clean shutdown.
+    ///
+    /// # Errors
+    /// Returns any I/O error raised while removing the journal.
+    pub fn close_journal(&mut self) -> io::Result<()> {
+        match self.journal.take() {
+            Some(journal) => journal.close(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `true` when there is a change that [`undo`](Self::undo) can revert.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    /// Returns `true` when there is an undone change that [`redo`](Self::redo)
+    /// can re-apply.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Reverts the most recent change to the entries or the time hack.
+    ///
+    /// Note creation, edits, section changes, deletions, moves, time hacks and
+    /// new file operations can all be undone. Nothing happens while an entry
+    /// is being edited.
+    ///
+    /// # Returns
+    /// `true` if a change was reverted.
+    ///
+    /// # Examples
+    /// ```
+    /// use real_time_note_taker::App;
+    ///
+    /// let mut app = App::new();
+    /// app.mark_time();
+    /// assert!(app.undo());
+    /// assert!(app.entries.is_empty());
+    /// assert!(app.redo());
+    /// assert_eq!(app.entries.len(), 1);
+    /// ```
+    ///
+    /// # See also
+    /// [`App::redo`]
+    pub fn undo(&mut self) -> bool {
+        if self.is_editing() {
+            return false;
+        }
+        let Some(step) = self.history.pop_undo() else {
+            return false;
+        };
+        let redo = self.run_step(step);
+        self.history.push_redo(redo);
+        self.clamp_selection();
+        true
+    }
+
+    /// Re-applies the most recently undone change.
+    ///
+    /// # Returns
+    /// `true` if a change was re-applied.
+    ///
+    /// # See also
+    /// [`App::undo`]
+    pub fn redo(&mut self) -> bool {
+        if self.is_editing() {
+            return false;
+        }
+        let Some(step) = self.history.pop_redo() else {
+            return false;
+        };
+        let undo = self.run_step(step);
+        self.history.push_undo(undo);
+        self.clamp_selection();
+        true
+    }
+
+    /// Applies a single mutation as one undoable operation.
+    fn apply_change(&mut self, change: Change) {
+        self.apply_step(vec![change], None);
+    }
+
+    /// Applies a group of mutations as one undoable operation.
+    ///
+    /// `previous_time_hack` is the time hack that was active before the
+    /// operation when it also changed the clock.
+    fn apply_step(&mut self, changes: Vec<Change>, previous_time_hack: Option<TimeHackState>) {
+        let mut undo = self.run_changes(changes);
+        if previous_time_hack.is_some() {
+            self.stream_time_hack();
+        }
+        undo.time_hack = previous_time_hack;
+        self.history.record(undo);
+    }
+
+    /// Applies a recorded step and returns the step that reverts it.
+    ///
+    /// The entry most affected by the step becomes the selection.
+    fn run_step(&mut self, step: Step) -> Step {
+        if let Some(focus) = step.changes.iter().rev().find_map(Change::focus) {
+            self.selected = Some(focus);
+        }
+        let dirty = self.dirty;
+        let mut inverse = self.run_changes(step.changes);
+        if let Some(session) = step.session {
+            self.dirty = dirty;
+            inverse.session = Some(self.replace_session(session));
+        }
+        inverse.time_hack = step
+            .time_hack
+            .map(|hack| std::mem::replace(&mut self.time_hack, hack));
+        if inverse.time_hack.is_some() {
+            self.stream_time_hack();
+        }
+        inverse
+    }
+
+    /// Replaces all entries and switches to another file as one undoable step.
+    fn switch_session(&mut self, entries: Vec<Entry>, session: SessionState) {
+        let dirty = self.dirty;
+        let mut undo = self.run_changes(vec![Change::Reset { entries }]);
+        self.dirty = dirty;
+        undo.session = Some(self.replace_session(session));
+        self.history.record(undo);
+    }
+
+    /// Switches to the file described by `session`, returning the current one.
+    fn replace_session(&mut self, session: SessionState) -> SessionState {
+        SessionState {
+            file_path: std::mem::replace(&mut self.file_path, session.file_path),
+            saved_ids: std::mem::replace(&mut self.saved_ids, session.saved_ids),
+            metadata: std::mem::replace(&mut self.metadata, session.metadata),
+            dirty: std::mem::replace(&mut self.dirty, session.dirty),
+        }
+    }
+
+    /// Journals and applies `changes`, returning the changes that revert them.
+    fn run_changes(&mut self, changes: Vec<Change>) -> Step {
+        let mut inverse = Vec::with_capacity(changes.len());
+        for change in changes {
+            if let Some(Err(e)) = self.journal.as_mut().map(|j| j.append(&change)) {
+                self.set_status(StatusKind::Error, format!("Failed to write journal: {e}"));
+            }
+            if let Some(stream) = &self.stream {
+                stream.record(&change, &self.entries);
+            }
+            inverse.push(change.inverse(&self.entries));
+            change.apply(&mut self.entries);
+        }
+        inverse.reverse();
+        self.dirty = true;
+        self.sync_notes();
+        Step {
+            changes: inverse,
+            time_hack: None,
+            session: None,
+        }
+    }
+
+    /// Keeps the selection within the bounds of [`entries`](Self::entries).
+    fn clamp_selection(&mut self) {
+        self.selected = match self.entries.len() {
+            0 => None,
+            len => self.selected.map(|i| i.min(len - 1)),
+        };
+    }
+
+    /// Rebuilds [`notes`](Self::notes) so it mirrors the notes in
+    /// [`entries`](Self::entries).
+    fn sync_notes(&mut self) {
+        self.notes = self
+            .entries
+            .iter()
+            .filter_map(|e| match e {
+                Entry::Note(n) => Some(n.clone()),
+                Entry::Section(_) => None,
+            })
+            .collect();
+    }
+
+    /// Processes a key event in normal mode.
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        match key.code {
+            _ if self.keys.up.matches(&key) => self.select_previous(),
+            _ if self.keys.down.matches(&key) => self.select_next(),
+            _ if self.keys.edit.matches(&key) => self.edit_selected(),
+            _ if self.keys.new_note.matches(&key) => self.start_note(),
+            _ if self.keys.new_section.matches(&key) => self.start_section(),
+            _ if self.keys.save.matches(&key) => self.start_save(),
+            _ if self.keys.load.matches(&key) => self.start_load(),
+            _ if self.keys.file_menu.matches(&key) => self.start_file_menu(),
+            _ if self.keys.bindings.matches(&key) => self.start_keybindings(),
+            _ if self.keys.theme.matches(&key) => self.start_theme_menu(),
+            _ if self.keys.time_hack.matches(&key) => self.start_time_hack(),
+            _ if self.keys.mark.matches(&key) => self.mark_time(),
+            _ if self.keys.describe.matches(&key) => self.describe_next_pending(),
+            _ if self.keys.delete.matches(&key) => self.start_delete(),
+            _ if self.keys.move_up.matches(&key) => self.move_selected_up(),
+            _ if self.keys.move_down.matches(&key) => self.move_selected_down(),
+            _ if self.keys.insert_above.matches(&key) => self.start_section_above(),
+            _ if self.keys.insert_below.matches(&key) => self.start_section_below(),
+            _ if self.keys.edit_time.matches(&key) => self.edit_selected_time(),
+            _ if self.keys.metadata.matches(&key) => self.start_metadata(),
+            _ if self.keys.undo.matches(&key) => {
+                self.undo();
+            }
+            _ if self.keys.redo.matches(&key) => {
+                self.redo();
+            }
+            _ => {}
+        }
+    }
+
+    /// Processes a key event in any editing mode.
+    // Conditions stay inside the arms so a key that cannot act here, such as
+    // Left at the start of the input, is consumed instead of reaching later arms.
+    #[allow(clippy::collapsible_match, clippy::too_many_lines)]
+    fn handle_editing_key(&mut self, key: KeyEvent) {
+        match key.code {
+            _ if self.keys.new_note.matches(&key) => match self.mode {
+                InputMode::EditingNote | InputMode::EditingExistingNote => {
+                    self.finalize_note();
+                }
+                InputMode::EditingSection | InputMode::EditingExistingSection => {
+                    self.finalize_section();
+                }
+                InputMode::Saving => {
+                    let path: String = self.input.drain(..).collect();
+                    let saved = !path.is_empty() && self.save_with_status(Path::new(&path));
+                    self.quit = match self.quit {
+                        QuitState::AfterSave if saved => QuitState::Done,
+                        QuitState::AfterSave => QuitState::Running,
+                        state => state,
+                    };
+                    self.cursor = 0;
+                    self.mode = InputMode::Normal;
+                }
+                InputMode::NewFile => {
+                    let path: String = self.input.drain(..).collect();
+                    if !path.is_empty() {
+                        let metadata = SessionMetadata {
+                            operator: self.metadata.operator.clone(),
+                            ..SessionMetadata::default()
+                        };
+                        self.switch_session(
+                            Vec::new(),
+                            SessionState {
+                                file_path: self.file_path.clone(),
+                                saved_ids: self.saved_ids.clone(),
+                                metadata,
+                                dirty: true,
+                            },
+                        );
+                        self.selected = None;
+                        self.save_with_status(Path::new(&path));
+                    }
+                    self.cursor = 0;
+                    self.mode = InputMode::Normal;
+                }
+                InputMode::TimeHack => {
+                    self.finalize_time_hack();
+                }
+                InputMode::EditingTimestamp => {
+                    self.finalize_timestamp();
+                }
+                InputMode::EditingMetadata => self.finalize_metadata(),
+                InputMode::Exporting => {
+                    let path: String = self.input.drain(..).collect();
+                    if !path.is_empty() {
+                        self.export_with_status(Path::new(&path));
+                    }
+                    self.cursor = 0;
+                    self.mode = InputMode::Normal;
                 }
+                InputMode::Normal
+                | InputMode::Loading
+                | InputMode::KeyBindings
+                | InputMode::KeyCapture
+                | InputMode::ConfirmReplace
+                | InputMode::ThemeSelect
+                | InputMode::BindWarning
+                | InputMode::FileMenu
+                | InputMode::RecoverSession
+                | InputMode::ConfirmDelete
+                | InputMode::Metadata
+                | InputMode::ConfirmQuit => {}
+            },
+            _ if self.keys.cancel.matches(&key) && self.mode == InputMode::EditingMetadata => {
+                self.input.clear();
+                self.cursor = 0;
+                self.mode = InputMode::Metadata;
+            }
+            _ if self.keys.cancel.matches(&key) => self.cancel_entry(),
+            _ if self.keys.mark.matches(&key) && !self.keys.mark.is_text() && self.is_editing() => {
+                self.mark_time();
+            }
+            KeyCode::Char('r') if matches!(self.mode, InputMode::TimeHack) => {
+                self.reset_time_hack();
+                self.input.clear();
+                self.cursor = 0;
                 self.mode = InputMode::Normal;
             }
-            c if c == self.keys.cancel => self.mode = InputMode::Normal,
+            KeyCode::Char(c)
+                if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+            {
+                if self.cursor >= self.input.len() {
+                    self.input.push(c);
+                } else {
+                    self.input.insert(self.cursor, c);
+                }
+                self.cursor += 1;
+            }
+            KeyCode::Backspace => {
+                if self.cursor > 0 && self.cursor <= self.input.len() {
+                    self.cursor -= 1;
+                    self.input.remove(self.cursor);
+                }
+            }
+            KeyCode::Left => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                }
+            }
+            KeyCode::Right => {
+                if self.cursor < self.input.len() {
+                    self.cursor += 1;
+                }
+            }
             _ => {}
         }
     }
 
-    fn handle_file_menu_key(&mut self, key: KeyEvent) {
+    #[allow(clippy::collapsible_match)]
+    fn handle_loading_key(&mut self, key: KeyEvent) {
         match key.code {
-            c if c == self.keys.up => {
-                if self.file_menu_selected > 0 {
-                    self.file_menu_selected -= 1;
+            _ if self.keys.up.matches(&key) => {
+                if self.load_selected > 0 {
+                    self.load_selected -= 1;
                 }
             }
-            c if c == self.keys.down => {
-                if self.file_menu_selected + 1 < 3 {
-                    self.file_menu_selected += 1;
+            _ if self.keys.down.matches(&key) => {
+                if self.load_selected + 1 < self.load_files.len() {
+                    self.load_selected += 1;
                 }
             }
-            code if code == KeyCode::Enter || code == self.keys.new_note => {
-                match self.file_menu_selected {
-                    0 => self.start_new_file(),
-                    1 => self.start_save(),
-                    2 => self.start_load(),
-                    _ => {}
+            _ if self.keys.new_note.matches(&key) => {
+                if let Some(path) = self.load_files.get(self.load_selected).cloned() {
+                    match self.load_from_file_in_place(&path) {
+                        Ok(()) => self.set_status(
+                            StatusKind::Info,
+                            format!(
+                                "Loaded {} entries from {}",
+                                self.entries.len(),
+                                path.display()
+                            ),
+                        ),
+                        Err(e) => self.set_status(StatusKind::Error, format!("Load failed: {e}")),
+                    }
                 }
+                self.mode = InputMode::Normal;
+            }
+            _ if self.keys.cancel.matches(&key) => {
+                self.mode = InputMode::Normal;
             }
-            c if c == self.keys.cancel => self.mode = InputMode::Normal,
             _ => {}
         }
     }
 
-    /// Handles a terminal event.
-    ///
-    /// # Errors
-    /// Propagates any I/O errors from the terminal event system.
-    pub fn handle_event(&mut self, event: &Event) -> Result<(), AppError> {
-        if let Event::Key(key) = *event {
-            if key.kind != KeyEventKind::Press {
-                return Ok(());
+    #[allow(clippy::collapsible_match)]
+    fn handle_keybindings_key(&mut self, key: KeyEvent) {
+        match key.code {
+            _ if self.keys.up.matches(&key) => {
+                if self.keybind_selected > 0 {
+                    self.keybind_selected -= 1;
+                }
             }
-            match self.mode {
-                InputMode::Normal => self.handle_normal_key(key),
-                InputMode::Loading => self.handle_loading_key(key),
-                InputMode::KeyBindings => self.handle_keybindings_key(key),
-                InputMode::KeyCapture => self.handle_capture_key(key),
-                InputMode::ConfirmReplace => self.handle_confirm_key(key),
-                InputMode::BindWarning => self.handle_bind_warning_key(key),
-                InputMode::ThemeSelect => self.handle_theme_key(key),
-                InputMode::FileMenu => self.handle_file_menu_key(key),
-                InputMode::EditingNote
-                | InputMode::EditingSection
-                | InputMode::EditingExistingNote
-                | InputMode::EditingExistingSection
-                | InputMode::Saving
-                | InputMode::NewFile
-                | InputMode::TimeHack => self.handle_editing_key(key),
+            _ if self.keys.down.matches(&key) => {
+                if self.keybind_selected + 1 < Action::ALL.len() {
+                    self.keybind_selected += 1;
+                }
             }
+            KeyCode::Enter => self.start_capture_binding(),
+            _ if self.keys.cancel.matches(&key) => self.mode = InputMode::Normal,
+            _ => {}
         }
-        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::NaiveTime;
-    use std::time::Instant;
+    fn handle_capture_key(&mut self, key: KeyEvent) {
+        if let Some(action) = self.capture_action {
+            match KeyCombo::from(key) {
+                c if c == self.keys.cancel => {
+                    self.capture_action = None;
+                    self.mode = InputMode::KeyBindings;
+                }
+                c if c == self.keys.bindings && action != Action::Bindings => {
+                    self.mode = InputMode::BindWarning;
+                }
+                code => {
+                    self.capture_action = None;
+                    if let Some(conflict) = self.keys.action_for_key(code) {
+                        if conflict != action {
+                            self.pending_key = Some(code);
+                            self.pending_action = Some(action);
+                            self.pending_conflict = Some(conflict);
+                            self.mode = InputMode::ConfirmReplace;
+                            return;
+                        }
+                    }
+                    self.keys.set(action, code);
+                    self.keys.save();
+                    self.mode = InputMode::KeyBindings;
+                }
+            }
+        }
+    }
+
+    fn handle_bind_warning_key(&mut self, key: KeyEvent) {
+        if self.keys.cancel.matches(&key) {
+            self.capture_action = None;
+            self.mode = InputMode::KeyBindings;
+        } else {
+            self.mode = InputMode::KeyCapture;
+        }
+    }
+
+    fn handle_confirm_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                if let (Some(new_key), Some(new_action), Some(conflict)) = (
+                    self.pending_key.take(),
+                    self.pending_action.take(),
+                    self.pending_conflict.take(),
+                ) {
+                    self.keys.set(conflict, KeyCode::Null);
+                    self.keys.set(new_action, new_key);
+                    self.keys.save();
+                }
+                self.mode = InputMode::KeyBindings;
+            }
+            _ if self.keys.cancel.matches(&key) => {
+                self.pending_key = None;
+                self.pending_action = None;
+                self.pending_conflict = None;
+                self.mode = InputMode::KeyBindings;
+            }
+            _ => {}
+        }
+    }
+
+    #[allow(clippy::collapsible_match)]
+    fn handle_theme_key(&mut self, key: KeyEvent) {
+        match key.code {
+            _ if self.keys.up.matches(&key) => {
+                if self.theme_selected > 0 {
+                    self.theme_selected -= 1;
+                }
+            }
+            _ if self.keys.down.matches(&key) => {
+                if self.theme_selected + 1 < ThemeName::ALL.len() {
+                    self.theme_selected += 1;
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(t) = ThemeName::ALL.get(self.theme_selected).copied() {
+                    self.theme = t;
+                    t.save();
+                }
+                self.mode = InputMode::Normal;
+            }
+            _ if self.keys.cancel.matches(&key) => self.mode = InputMode::Normal,
+            _ => {}
+        }
+    }
+
+    /// Re-times notes between time hacks from the file menu and reports the
+    /// outcome.
+    fn start_interpolate(&mut self) {
+        self.mode = InputMode::Normal;
+        let hacks = self.time_hacks().len();
+        if hacks < 2 {
+            self.set_status(
+                StatusKind::Error,
+                "Re-timing notes needs at least two time hacks",
+            );
+            return;
+        }
+        let moved = self.interpolate_time_hacks();
+        self.set_status(
+            StatusKind::Info,
+            format!("Re-timed {moved} notes between {hacks} time hacks"),
+        );
+    }
+
+    #[allow(clippy::collapsible_match)]
+    fn handle_file_menu_key(&mut self, key: KeyEvent) {
+        match key.code {
+            _ if self.keys.up.matches(&key) => {
+                if self.file_menu_selected > 0 {
+                    self.file_menu_selected -= 1;
+                }
+            }
+            _ if self.keys.down.matches(&key) => {
+                if self.file_menu_selected + 1 < FILE_MENU_LEN {
+                    self.file_menu_selected += 1;
+                }
+            }
+            code if code == KeyCode::Enter || self.keys.new_note.matches(&key) => {
+                match self.file_menu_selected {
+                    0 => self.start_new_file(),
+                    1 => self.start_save(),
+                    2 => self.start_load(),
+                    i => match ExportFormat::ALL.get(i - 3).copied() {
+                        Some(format) => self.start_export(format),
+                        None => self.start_interpolate(),
+                    },
+                }
+            }
+            _ if self.keys.cancel.matches(&key) => self.mode = InputMode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_delete_key(&mut self, key: KeyEvent) {
+        match key.code {
+            code if code == KeyCode::Enter || self.keys.delete.matches(&key) => {
+                self.delete_selected();
+            }
+            _ if self.keys.cancel.matches(&key) => self.mode = InputMode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_metadata_key(&mut self, key: KeyEvent) {
+        let fields = self.metadata.fields.len();
+        match key.code {
+            _ if self.keys.up.matches(&key) && self.metadata_selected > 0 => {
+                self.metadata_selected -= 1;
+            }
+            _ if self.keys.down.matches(&key)
+                && self.metadata_selected + 1 < self.metadata_rows() =>
+            {
+                self.metadata_selected += 1;
+            }
+            code if code == KeyCode::Enter || self.keys.edit.matches(&key) => {
+                self.edit_selected_metadata();
+            }
+            _ if self.keys.delete.matches(&key)
+                && (2..fields + 2).contains(&self.metadata_selected) =>
+            {
+                self.metadata.fields.remove(self.metadata_selected - 2);
+                self.dirty = true;
+            }
+            _ if self.keys.cancel.matches(&key) || self.keys.metadata.matches(&key) => {
+                self.mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_quit_key(&mut self, key: KeyEvent) {
+        match key.code {
+            _ if self.keys.up.matches(&key) && self.quit_selected > 0 => {
+                self.quit_selected -= 1;
+            }
+            _ if self.keys.down.matches(&key) && self.quit_selected + 1 < 3 => {
+                self.quit_selected += 1;
+            }
+            code if code == KeyCode::Enter || self.keys.new_note.matches(&key) => {
+                match self.quit_selected {
+                    0 => self.save_and_quit(),
+                    1 => self.quit = QuitState::Done,
+                    _ => self.mode = InputMode::Normal,
+                }
+            }
+            _ if self.keys.cancel.matches(&key) => self.mode = InputMode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_recover_key(&mut self, key: KeyEvent) {
+        match key.code {
+            code if code == KeyCode::Enter || self.keys.new_note.matches(&key) => {
+                self.restore_recovered();
+            }
+            _ if self.keys.cancel.matches(&key) => self.discard_recovered(),
+            _ => {}
+        }
+    }
+
+    /// Handles a terminal event.
+    ///
+    /// # Errors
+    /// Propagates any I/O errors from the terminal event system.
+    pub fn handle_event(&mut self, event: &Event) -> Result<(), AppError> {
+        if let Event::Key(key) = *event {
+            if key.kind != KeyEventKind::Press {
+                return Ok(());
+            }
+            self.status = self.status.take().filter(|s| s.kind != StatusKind::Error);
+            let typing = matches!(
+                self.mode,
+                InputMode::EditingNote
+                    | InputMode::EditingSection
+                    | InputMode::EditingExistingNote
+                    | InputMode::EditingExistingSection
+                    | InputMode::Saving
+                    | InputMode::NewFile
+                    | InputMode::TimeHack
+                    | InputMode::EditingTimestamp
+                    | InputMode::EditingMetadata
+                    | InputMode::Exporting
+            );
+            if self.keys.quit.matches(&key)
+                && !(typing && self.keys.quit.is_text())
+                && self.mode != InputMode::ConfirmQuit
+            {
+                self.request_quit();
+                return Ok(());
+            }
+            match self.mode {
+                InputMode::Normal => self.handle_normal_key(key),
+                InputMode::Loading => self.handle_loading_key(key),
+                InputMode::KeyBindings => self.handle_keybindings_key(key),
+                InputMode::KeyCapture => self.handle_capture_key(key),
+                InputMode::ConfirmReplace => self.handle_confirm_key(key),
+                InputMode::BindWarning => self.handle_bind_warning_key(key),
+                InputMode::ThemeSelect => self.handle_theme_key(key),
+                InputMode::FileMenu => self.handle_file_menu_key(key),
+                InputMode::RecoverSession => self.handle_recover_key(key),
+                InputMode::ConfirmDelete => self.handle_delete_key(key),
+                InputMode::Metadata => self.handle_metadata_key(key),
+                InputMode::ConfirmQuit => self.handle_quit_key(key),
+                InputMode::EditingNote
+                | InputMode::EditingSection
+                | InputMode::EditingExistingNote
+                | InputMode::EditingExistingSection
+                | InputMode::Saving
+                | InputMode::NewFile
+                | InputMode::TimeHack
+                | InputMode::EditingTimestamp
+                | InputMode::EditingMetadata
+                | InputMode::Exporting => self.handle_editing_key(key),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses an absolute or relative timestamp correction for a note at `base`,
+/// reading absolute times as times of day in `zone`.
+fn parse_timestamp_edit(
+    input: &str,
+    base: DateTime<Local>,
+    zone: DisplayZone,
+) -> Option<DateTime<Local>> {
+    let input = input.trim();
+    if let Some(rest) = input.strip_prefix(['+', '-']) {
+        let (number, unit_ms) = if let Some(n) = rest.strip_suffix("ms") {
+            (n, 1.0)
+        } else if let Some(n) = rest.strip_suffix('s') {
+            (n, 1_000.0)
+        } else if let Some(n) = rest.strip_suffix('m') {
+            (n, 60_000.0)
+        } else if let Some(n) = rest.strip_suffix('h') {
+            (n, 3_600_000.0)
+        } else {
+            (rest, 1_000.0)
+        };
+        let value: f64 = number.trim().parse().ok()?;
+        if !value.is_finite() {
+            return None;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let millis = (value * unit_ms).round() as i64;
+        let delta = Duration::try_milliseconds(millis)?;
+        return if input.starts_with('-') {
+            base.checked_sub_signed(delta)
+        } else {
+            base.checked_add_signed(delta)
+        };
+    }
+    let time = NaiveTime::parse_from_str(input, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M:%S"))
+        .ok()?;
+    zone.from_wall_clock(zone.wall_clock(base).date().and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimulatedClock;
+    use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+    use std::time::Instant;
+
+    #[test]
+    fn start_and_finalize_note() {
+        let mut app = App::new();
+        assert!(app.notes.is_empty());
+        app.start_note();
+        assert!(matches!(app.mode(), InputMode::EditingNote));
+        assert!(app.note_time.is_some());
+        app.input.push_str("test");
+        app.finalize_note();
+        assert!(app.note_time.is_none());
+        assert!(matches!(app.mode(), InputMode::Normal));
+        assert_eq!(app.notes.len(), 1);
+        assert_eq!(app.notes[0].text, "test");
+    }
+
+    #[test]
+    fn cancel_entry() {
+        let mut app = App::new();
+        app.start_note();
+        app.input.push_str("discard");
+        app.cancel_entry();
+        assert!(app.notes.is_empty());
+        assert!(app.input.is_empty());
+        assert!(app.note_time.is_none());
+        assert!(matches!(app.mode(), InputMode::Normal));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut app = App::new();
+        app.start_note();
+        app.input.push_str("hello");
+        app.finalize_note();
+        app.start_section();
+        app.input.push_str("section");
+        app.finalize_section();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.csv");
+        app.save_to_file(&path).unwrap();
+
+        let loaded = App::load_from_file(&path).unwrap();
+        assert_eq!(loaded.entries, app.entries);
+        assert_eq!(loaded.notes, app.notes);
+    }
+
+    #[test]
+    fn selection_navigation() {
+        let mut app = App::new();
+        app.start_note();
+        app.finalize_note();
+        app.start_section();
+        app.input.push('a');
+        app.finalize_section();
+
+        assert_eq!(app.selected(), Some(1));
+        app.select_previous();
+        assert_eq!(app.selected(), Some(0));
+        app.select_previous();
+        assert_eq!(app.selected(), Some(0));
+        app.select_next();
+        assert_eq!(app.selected(), Some(1));
+        app.select_next();
+        assert_eq!(app.selected(), Some(1));
+    }
+
+    #[test]
+    fn edit_existing_note() {
+        let mut app = App::new();
+        app.start_note();
+        app.input.push_str("first");
+        app.finalize_note();
+
+        app.start_note();
+        app.input.push_str("second");
+        app.finalize_note();
+
+        app.selected = Some(0);
+        app.edit_selected();
+        assert!(matches!(app.mode(), InputMode::EditingExistingNote));
+        app.input.clear();
+        app.input.push_str("updated");
+        app.finalize_note();
+        assert_eq!(app.notes[0].text, "updated");
+    }
+
+    #[test]
+    fn edit_existing_section() {
+        let mut app = App::new();
+        app.start_section();
+        app.input.push_str("sec");
+        app.finalize_section();
+        app.selected = Some(0);
+        app.edit_selected();
+        assert!(matches!(app.mode(), InputMode::EditingExistingSection));
+        app.input.clear();
+        app.input.push_str("new");
+        app.finalize_section();
+        if let Entry::Section(sec) = &app.entries[0] {
+            assert_eq!(sec.title, "new");
+        } else {
+            panic!("Expected section");
+        }
+    }
+
+    #[test]
+    fn load_in_place() {
+        let mut app = App::new();
+        app.start_note();
+        app.input.push_str("data");
+        app.finalize_note();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.csv");
+        app.save_to_file(&path).unwrap();
+
+        let mut other = App::new();
+        other.load_from_file_in_place(&path).unwrap();
+        assert_eq!(other.entries.len(), 1);
+        if let Entry::Note(n) = &other.entries[0] {
+            assert_eq!(n.text, "data");
+        } else {
+            panic!("Expected note");
+        }
+    }
+
+    #[test]
+    fn handle_event_note_flow() {
+        let mut app = App::new();
+        let enter = Event::Key(KeyEvent::from(KeyCode::Enter));
+        app.handle_event(&enter).unwrap();
+        assert!(matches!(app.mode(), InputMode::EditingNote));
+        let a = Event::Key(KeyEvent::from(KeyCode::Char('a')));
+        let b = Event::Key(KeyEvent::from(KeyCode::Char('b')));
+        app.handle_event(&a).unwrap();
+        app.handle_event(&b).unwrap();
+        app.handle_event(&enter).unwrap();
+        assert_eq!(app.notes.len(), 1);
+        assert_eq!(app.notes[0].text, "ab");
+    }
+
+    #[test]
+    fn handle_left_right_navigation() {
+        let mut app = App::new();
+        let enter = Event::Key(KeyEvent::from(KeyCode::Enter));
+        app.handle_event(&enter).unwrap();
+        let a = Event::Key(KeyEvent::from(KeyCode::Char('a')));
+        let b = Event::Key(KeyEvent::from(KeyCode::Char('b')));
+        let c = Event::Key(KeyEvent::from(KeyCode::Char('c')));
+        app.handle_event(&a).unwrap();
+        app.handle_event(&b).unwrap();
+        app.handle_event(&c).unwrap();
+        let left = Event::Key(KeyEvent::from(KeyCode::Left));
+        app.handle_event(&left).unwrap();
+        app.handle_event(&left).unwrap();
+        let x = Event::Key(KeyEvent::from(KeyCode::Char('X')));
+        app.handle_event(&x).unwrap();
+        let right = Event::Key(KeyEvent::from(KeyCode::Right));
+        app.handle_event(&right).unwrap();
+        app.handle_event(&enter).unwrap();
+        assert_eq!(app.notes[0].text, "aXbc");
+    }
+
+    #[test]
+    fn change_keybinding() {
+        let mut keys = KeyBindings::default();
+        keys.set(Action::Save, KeyCode::Char('z'));
+        keys.save();
+        let loaded = KeyBindings::load_or_default();
+        assert_eq!(loaded.get(Action::Save), KeyCode::Char('z'));
+        let _ = std::fs::remove_file(KeyBindings::config_path_for_test());
+    }
+
+    #[test]
+    fn rebind_conflict() {
+        let mut app = App::new();
+        app.start_keybindings();
+        app.capture_action = Some(Action::Load);
+        // Press key already bound to Save
+        let key = KeyEvent::from(KeyCode::Char('w'));
+        app.handle_capture_key(key);
+        assert!(matches!(app.mode(), InputMode::ConfirmReplace));
+        assert_eq!(app.pending_action, Some(Action::Load));
+        assert_eq!(app.pending_conflict, Some(Action::Save));
+        // Confirm replacement
+        app.handle_confirm_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.keys.get(Action::Load), KeyCode::Char('w'));
+        assert_eq!(app.keys.get(Action::Save), KeyCode::Null);
+    }
+
+    #[test]
+    fn rebind_bindings_key_warning() {
+        let mut app = App::new();
+        app.start_keybindings();
+        app.capture_action = Some(Action::Save);
+        let key = KeyEvent::from(app.keys.bindings);
+        app.handle_capture_key(key);
+        assert!(matches!(app.mode(), InputMode::BindWarning));
+        assert_eq!(app.capture_action, Some(Action::Save));
+        app.handle_bind_warning_key(KeyEvent::from(KeyCode::Char('x')));
+        assert!(matches!(app.mode(), InputMode::KeyCapture));
+        assert_eq!(app.capture_action, Some(Action::Save));
+    }
+
+    #[test]
+    fn time_hack_sets_clock() {
+        let clock = SimulatedClock::new(Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap());
+        let mut app = App::new();
+        app.set_clock(clock.clone());
+        app.time_hack = Some(TimeHack::new(
+            Local.with_ymd_and_hms(2024, 5, 1, 1, 2, 3).unwrap(),
+            clock.now(),
+        ));
+        clock.advance(Duration::milliseconds(250));
+        app.start_note();
+        assert_eq!(
+            app.note_time,
+            Some(
+                Local.with_ymd_and_hms(2024, 5, 1, 1, 2, 3).unwrap() + Duration::milliseconds(250)
+            )
+        );
+    }
+
+    #[test]
+    fn notes_are_stamped_from_clock_source() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let clock = SimulatedClock::new(start);
+        let mut app = App::new();
+        app.set_clock(clock.clone());
+        assert_eq!(app.time_source(), "Simulated");
+        assert!(app.clock_health().is_good());
+        app.mark_time();
+        clock.advance(Duration::seconds(5));
+        app.mark_time();
+        assert_eq!(app.notes[0].timestamp, start);
+        assert_eq!(app.notes[1].timestamp, start + Duration::seconds(5));
+
+        app.input = "09:00:00".into();
+        app.finalize_time_hack();
+        clock.advance(Duration::seconds(1));
+        // Hacked time runs from the hack at the rate of the source.
+        assert_eq!(
+            app.current_time().time(),
+            NaiveTime::from_hms_opt(9, 0, 1).unwrap()
+        );
+
+        app.set_clock(SystemClock);
+        assert!(app.time_hack().is_none());
+        assert_eq!(app.time_source(), "System");
+    }
+
+    #[test]
+    fn finalize_time_hack_adds_entry() {
+        let mut app = App::new();
+        app.input.push_str("01:02:03");
+        app.finalize_time_hack();
+        assert!(app.time_hack.is_some());
+        assert_eq!(app.entries.len(), 1);
+        if let Entry::Note(n) = &app.entries[0] {
+            assert!(n.text.contains("Time Hack"));
+        } else {
+            panic!("Expected note");
+        }
+    }
+    #[test]
+    fn finalize_time_hack_invalid() {
+        let mut app = App::new();
+        app.input.push_str("bad");
+        app.finalize_time_hack();
+        assert!(app.time_hack.is_none());
+        assert!(app.entries.is_empty());
+        assert!(app.input.is_empty());
+        assert!(matches!(app.mode(), InputMode::Normal));
+        assert_eq!(app.status().unwrap().kind, StatusKind::Error);
+    }
+
+    #[test]
+    fn notes_between_hacks_are_interpolated() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let clock = SimulatedClock::new(start);
+        let mut app = App::new();
+        app.set_clock(clock.clone());
+        let hack = |app: &mut App, input: &str| {
+            app.input = input.into();
+            app.finalize_time_hack();
+        };
+        let note = |app: &mut App, text: &str| {
+            app.start_note();
+            app.input = text.into();
+            app.finalize_note();
+        };
+        note(&mut app, "before");
+        hack(&mut app, "10:00:00");
+        clock.advance(Duration::minutes(30));
+        note(&mut app, "half way");
+        clock.advance(Duration::minutes(30));
+        // The reference gained 4 s on the clock source over the hour.
+        hack(&mut app, "11:00:04");
+        clock.advance(Duration::minutes(1));
+        note(&mut app, "after");
+
+        let hacks = app.time_hacks();
+        assert_eq!(hacks.len(), 2);
+        assert_eq!(hacks[1].offset - hacks[0].offset, Duration::seconds(4));
+        let drift = app.clock_drift().unwrap();
+        assert!((drift - 4.0 / 3600.0).abs() < 1e-12, "{drift}");
+
+        assert_eq!(app.interpolate_time_hacks(), 1);
+        let times: Vec<_> = app
+            .notes
+            .iter()
+            .map(|n| n.timestamp.format("%H:%M:%S").to_string())
+            .collect();
+        assert_eq!(
+            times,
+            ["09:00:00", "09:00:00", "10:30:02", "10:00:00", "11:01:04"]
+        );
+        assert!(app.notes[2].original_timestamp.is_some());
+        assert_eq!(app.interpolate_time_hacks(), 0);
+        app.undo();
+        assert_eq!(
+            app.notes[2].timestamp.format("%H:%M:%S").to_string(),
+            "10:30:00"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        for format in FileFormat::ALL {
+            let path = dir.path().join(format!("hacks.{}", format.extension()));
+            app.save_to_file(&path).unwrap();
+            assert_eq!(
+                App::load_from_file(&path).unwrap().time_hacks(),
+                hacks,
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn notes_after_hack_reset_are_not_interpolated() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let clock = SimulatedClock::new(start);
+        let mut app = App::new();
+        app.set_clock(clock.clone());
+        let hack = |app: &mut App, input: &str| {
+            app.input = input.into();
+            app.finalize_time_hack();
+        };
+        let note = |app: &mut App, text: &str| {
+            app.start_note();
+            app.input = text.into();
+            app.finalize_note();
+        };
+        hack(&mut app, "09:00:10");
+        clock.advance(Duration::minutes(15));
+        note(&mut app, "hacked");
+        clock.advance(Duration::minutes(15));
+        hack(&mut app, "");
+        assert!(app.time_hack.is_none());
+        assert!(app.notes[2].hack_reset);
+        assert_eq!(app.notes[2].text, "Time Hack Reset: 09:30:10 -> 09:30:00");
+        clock.advance(Duration::minutes(15));
+        note(&mut app, "after reset");
+        clock.advance(Duration::minutes(15));
+        hack(&mut app, "10:00:14");
+
+        assert_eq!(app.interpolate_time_hacks(), 1);
+        let times: Vec<_> = app
+            .notes
+            .iter()
+            .map(|n| n.timestamp.format("%H:%M:%S").to_string())
+            .collect();
+        assert_eq!(
+            times,
+            ["09:00:00", "09:15:11", "09:30:00", "09:45:00", "10:00:00"]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        for format in FileFormat::ALL {
+            let path = dir.path().join(format!("reset.{}", format.extension()));
+            app.save_to_file(&path).unwrap();
+            let loaded = App::load_from_file(&path).unwrap();
+            let resets: Vec<_> = loaded.notes.iter().map(|n| n.hack_reset).collect();
+            assert_eq!(resets, [false, false, true, false, false], "{format:?}");
+        }
+    }
+
+    #[test]
+    fn interpolating_needs_two_hacks() {
+        let mut app = App::new();
+        app.start_interpolate();
+        assert_eq!(app.status().unwrap().kind, StatusKind::Error);
+    }
+
+    #[test]
+    fn time_hack_across_midnight_keeps_the_date() {
+        let before = Local.with_ymd_and_hms(2024, 5, 1, 23, 59, 50).unwrap();
+        let clock = SimulatedClock::new(before);
+        let mut app = App::new();
+        app.set_clock(clock.clone());
+        app.input = "00:00:05".into();
+        app.finalize_time_hack();
+        let hack = app.time_hack().unwrap();
+        assert_eq!(hack.offset, Duration::seconds(15));
+        assert_eq!(
+            entry_labels(&app),
+            ["Time Hack: 2024-05-01 23:59:50 -> 2024-05-02 00:00:05"]
+        );
+        clock.advance(Duration::seconds(10));
+        assert_eq!(
+            app.current_time(),
+            Local.with_ymd_and_hms(2024, 5, 2, 0, 0, 15).unwrap()
+        );
+
+        app.input = "2024-05-01T12:00:00Z".into();
+        app.finalize_time_hack();
+        assert_eq!(
+            app.current_time(),
+            Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn start_load_filters_saved_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("a.csv");
+        let jsonl = dir.path().join("c.jsonl");
+        let txt = dir.path().join("b.txt");
+        std::fs::write(&csv, "").unwrap();
+        std::fs::write(&jsonl, "").unwrap();
+        std::fs::write(&txt, "").unwrap();
+        let mut app = App::new();
+        app.save_dir = dir.path().to_path_buf();
+        app.start_load();
+        assert!(matches!(app.mode(), InputMode::Loading));
+        app.load_files.sort();
+        assert_eq!(app.load_files, vec![csv, jsonl]);
+    }
+
+    #[test]
+    fn keybinding_action_lookup() {
+        let bindings = KeyBindings::default();
+        for a in Action::ALL {
+            assert_eq!(bindings.action_for_key(bindings.get(a)), Some(a));
+        }
+        assert_eq!(bindings.action_for_key(KeyCode::F(1)), None);
+    }
+
+    #[test]
+    fn time_source_changes() {
+        let mut app = App::new();
+        assert_eq!(app.time_source(), "System");
+        app.time_hack = Some(TimeHack::new(Local::now(), Local::now()));
+        assert_eq!(app.time_source(), "Hacked");
+    }
+
+    #[test]
+    fn mark_time_queues_pending_notes() {
+        let mut app = App::new();
+        app.mark_time();
+        app.mark_time();
+        assert!(matches!(app.mode(), InputMode::Normal));
+        assert_eq!(app.entries.len(), 2);
+        assert_eq!(app.pending_count(), 2);
+        assert_eq!(app.selected(), Some(1));
+    }
+
+    #[test]
+    fn describe_walks_through_pending_notes() {
+        let mut app = App::new();
+        app.mark_time();
+        app.mark_time();
+        app.describe_next_pending();
+        assert!(matches!(app.mode(), InputMode::EditingExistingNote));
+        assert_eq!(app.selected(), Some(0));
+        app.input.push_str("first");
+        app.finalize_note();
+        assert!(matches!(app.mode(), InputMode::EditingExistingNote));
+        assert_eq!(app.selected(), Some(1));
+        app.input.push_str("second");
+        app.finalize_note();
+        assert!(matches!(app.mode(), InputMode::Normal));
+        assert_eq!(app.pending_count(), 0);
+        assert_eq!(app.notes[0].text, "first");
+        assert_eq!(app.notes[1].text, "second");
+    }
+
+    #[test]
+    fn describe_with_empty_text_keeps_pending() {
+        let mut app = App::new();
+        app.mark_time();
+        app.describe_next_pending();
+        app.finalize_note();
+        assert!(matches!(app.mode(), InputMode::Normal));
+        assert_eq!(app.pending_count(), 1);
+    }
+
+    #[test]
+    fn mark_while_typing_note() {
+        let mut app = App::new();
+        app.keys.mark = KeyCode::F(2).into();
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Enter)))
+            .unwrap();
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Char('a'))))
+            .unwrap();
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::F(2))))
+            .unwrap();
+        assert!(matches!(app.mode(), InputMode::EditingNote));
+        assert_eq!(app.pending_count(), 1);
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Enter)))
+            .unwrap();
+        assert_eq!(app.entries.len(), 2);
+        assert_eq!(app.notes[0].text, "a");
+        assert!(app.notes[1].pending);
+        assert_eq!(app.selected(), Some(0));
+    }
+
+    #[test]
+    fn pending_notes_survive_save_and_load() {
+        let mut app = App::new();
+        app.mark_time();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pending.csv");
+        app.save_to_file(&path).unwrap();
+        let loaded = App::load_from_file(&path).unwrap();
+        assert_eq!(loaded.pending_count(), 1);
+    }
+
+    #[test]
+    fn journal_recovers_unclean_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = App::new();
+        app.save_dir = dir.path().to_path_buf();
+        app.open_journal().unwrap();
+        app.start_note();
+        app.input.push_str("before");
+        app.finalize_note();
+        app.start_section();
+        app.input.push_str("sec");
+        app.finalize_section();
+        app.selected = Some(0);
+        app.edit_selected();
+        app.input.push_str(" edited");
+        app.finalize_note();
+        app.mark_time();
+        // Simulate a crash by dropping the app without closing the journal.
+        let expected = app.entries.clone();
+        drop(app);
+
+        let mut next = App::new();
+        next.save_dir = dir.path().to_path_buf();
+        next.open_journal().unwrap();
+        assert!(matches!(next.mode(), InputMode::RecoverSession));
+        assert_eq!(next.recovered_entries(), Some(expected.as_slice()));
+        next.handle_recover_key(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(next.mode(), InputMode::Normal));
+        assert_eq!(next.entries, expected);
+        assert_eq!(next.notes.len(), 2);
+        assert!(next.is_dirty());
+        assert!(next.undo());
+        assert!(next.entries.is_empty());
+        assert!(next.redo());
+        assert_eq!(next.entries, expected);
+        let path = next.journal_path().unwrap().to_path_buf();
+        next.close_journal().unwrap();
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn journal_recovers_time_hack() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let mut app = App::new();
+        app.save_dir = dir.path().to_path_buf();
+        app.set_clock(SimulatedClock::new(start));
+        app.open_journal().unwrap();
+        for input in ["10:00:00", "", "11:00:00"] {
+            app.input = input.into();
+            app.finalize_time_hack();
+        }
+        let hack = app.time_hack;
+        assert!(hack.is_some());
+        drop(app);
+
+        let mut next = App::new();
+        next.save_dir = dir.path().to_path_buf();
+        next.open_journal().unwrap();
+        next.restore_recovered();
+        assert_eq!(next.time_hack, hack);
+        assert!(next.undo());
+        assert_eq!(next.time_hack, None);
+
+        // A hack that was reset before the crash stays reset.
+        next.redo();
+        next.input.clear();
+        next.finalize_time_hack();
+        drop(next);
+        let mut last = App::new();
+        last.save_dir = dir.path().to_path_buf();
+        last.open_journal().unwrap();
+        last.restore_recovered();
+        assert_eq!(last.time_hack, None);
+        assert!(last.notes.last().unwrap().hack_reset);
+    }
+
+    #[test]
+    fn journal_of_running_session_is_not_recovered() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = App::new();
+        first.save_dir = dir.path().to_path_buf();
+        first.open_journal().unwrap();
+        first.mark_time();
+
+        let mut second = App::new();
+        second.save_dir = dir.path().to_path_buf();
+        second.open_journal().unwrap();
+        assert!(matches!(second.mode(), InputMode::Normal));
+        assert_ne!(first.journal_path(), second.journal_path());
+        second.mark_time();
+        second.close_journal().unwrap();
+
+        // The first session's journal is intact and recovered once it crashes.
+        let expected = first.entries.clone();
+        drop(first);
+        let mut third = App::new();
+        third.save_dir = dir.path().to_path_buf();
+        third.open_journal().unwrap();
+        assert_eq!(third.recovered_entries(), Some(expected.as_slice()));
+    }
+
+    #[test]
+    fn journals_are_recovered_per_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = App::new();
+        app.save_dir = dir.path().to_path_buf();
+        app.save_on_quit(dir.path().join("flight.csv"));
+        app.open_journal().unwrap();
+        app.mark_time();
+        drop(app);
+
+        let mut other = App::new();
+        other.save_dir = dir.path().to_path_buf();
+        other.open_journal().unwrap();
+        assert!(other.recovered_entries().is_none());
+
+        let mut same = App::new();
+        same.save_dir = dir.path().to_path_buf();
+        same.save_on_quit(dir.path().join("flight.csv"));
+        same.open_journal().unwrap();
+        assert_eq!(same.recovered_entries().map(<[_]>::len), Some(1));
+    }
+
+    #[test]
+    fn journal_discard_starts_fresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = App::new();
+        app.save_dir = dir.path().to_path_buf();
+        app.open_journal().unwrap();
+        app.mark_time();
+        drop(app);
+
+        let mut next = App::new();
+        next.save_dir = dir.path().to_path_buf();
+        next.open_journal().unwrap();
+        next.discard_recovered();
+        assert!(next.entries.is_empty());
+        drop(next);
+
+        let mut last = App::new();
+        last.save_dir = dir.path().to_path_buf();
+        last.open_journal().unwrap();
+        assert!(matches!(last.mode(), InputMode::Normal));
+        assert!(last.recovered_entries().is_none());
+    }
+
+    fn app_with_entries(texts: &[&str]) -> App {
+        let mut app = App::new();
+        for t in texts {
+            if let Some(title) = t.strip_prefix('#') {
+                app.start_section();
+                app.input.push_str(title);
+                app.finalize_section();
+            } else {
+                app.start_note();
+                app.input.push_str(t);
+                app.finalize_note();
+            }
+        }
+        app
+    }
+
+    fn entry_labels(app: &App) -> Vec<String> {
+        app.entries
+            .iter()
+            .map(|e| match e {
+                Entry::Note(n) => n.text.clone(),
+                Entry::Section(s) => format!("#{}", s.title),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn delete_requires_confirmation() {
+        let mut app = app_with_entries(&["a", "#s", "b"]);
+        app.selected = Some(0);
+        app.handle_normal_key(KeyEvent::from(app.keys.delete));
+        assert!(matches!(app.mode(), InputMode::ConfirmDelete));
+        app.handle_delete_key(KeyEvent::from(app.keys.cancel));
+        assert_eq!(app.entries.len(), 3);
+
+        app.handle_normal_key(KeyEvent::from(app.keys.delete));
+        app.handle_delete_key(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.mode(), InputMode::Normal));
+        assert_eq!(entry_labels(&app), ["#s", "b"]);
+        assert_eq!(app.notes.len(), 1);
+        assert_eq!(app.notes[0].text, "b");
+        assert_eq!(app.selected(), Some(0));
+    }
+
+    #[test]
+    fn delete_last_entry_moves_selection() {
+        let mut app = app_with_entries(&["a", "b"]);
+        app.delete_selected();
+        assert_eq!(app.selected(), Some(0));
+        app.delete_selected();
+        assert_eq!(app.selected(), None);
+        assert!(app.notes.is_empty());
+    }
+
+    #[test]
+    fn move_entries_up_and_down() {
+        let mut app = app_with_entries(&["a", "b", "#s"]);
+        app.move_selected_up();
+        app.move_selected_up();
+        assert_eq!(entry_labels(&app), ["#s", "a", "b"]);
+        assert_eq!(app.selected(), Some(0));
+        app.move_selected_up();
+        assert_eq!(app.selected(), Some(0));
+        app.move_selected_down();
+        assert_eq!(entry_labels(&app), ["a", "#s", "b"]);
+        app.selected = Some(2);
+        app.move_selected_down();
+        assert_eq!(entry_labels(&app), ["a", "#s", "b"]);
+        app.move_selected_up();
+        assert_eq!(entry_labels(&app), ["a", "b", "#s"]);
+        assert_eq!(app.notes[0].text, "a");
+        assert_eq!(app.notes[1].text, "b");
+    }
+
+    #[test]
+    fn insert_section_above_and_below() {
+        let mut app = app_with_entries(&["a", "b"]);
+        app.selected = Some(1);
+        app.start_section_above();
+        app.input.push_str("above");
+        app.finalize_section();
+        assert_eq!(entry_labels(&app), ["a", "#above", "b"]);
+        assert_eq!(app.selected(), Some(1));
+        app.selected = Some(0);
+        app.start_section_below();
+        app.input.push_str("below");
+        app.finalize_section();
+        assert_eq!(entry_labels(&app), ["a", "#below", "#above", "b"]);
+        app.start_section();
+        app.input.push_str("end");
+        app.finalize_section();
+        assert_eq!(entry_labels(&app).last().unwrap(), "#end");
+    }
+
+    #[test]
+    fn undo_and_redo_entry_mutations() {
+        let mut app = app_with_entries(&["a", "#s"]);
+        app.selected = Some(0);
+        app.edit_selected();
+        app.input.clear();
+        app.input.push_str("edited");
+        app.finalize_note();
+        app.move_selected_down();
+        app.delete_selected();
+        assert_eq!(entry_labels(&app), ["#s"]);
+
+        assert!(app.undo());
+        assert_eq!(entry_labels(&app), ["#s", "edited"]);
+        assert!(app.undo());
+        assert_eq!(entry_labels(&app), ["edited", "#s"]);
+        assert!(app.undo());
+        assert_eq!(entry_labels(&app), ["a", "#s"]);
+        assert_eq!(app.notes[0].text, "a");
+        assert!(app.undo());
+        assert!(app.undo());
+        assert!(app.entries.is_empty());
+        assert!(!app.undo());
+        assert_eq!(app.selected(), None);
+
+        while app.redo() {}
+        assert_eq!(entry_labels(&app), ["#s"]);
+        assert!(!app.can_redo());
+    }
+
+    #[test]
+    fn new_change_clears_redo() {
+        let mut app = app_with_entries(&["a"]);
+        app.undo();
+        assert!(app.can_redo());
+        app.mark_time();
+        assert!(!app.can_redo());
+    }
+
+    #[test]
+    fn undo_new_file_restores_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&["keep", "#me"]);
+        app.start_new_file();
+        app.input = dir.path().join("new.csv").to_string_lossy().into();
+        app.metadata.title = "Sortie 3".into();
+        app.metadata.set_field("aircraft", "N123AB");
+        let created = app.metadata.created;
+        let old = dir.path().join("old.csv");
+        app.save_on_quit(&old);
+        app.handle_editing_key(KeyEvent::from(KeyCode::Enter));
+        assert!(app.entries.is_empty());
+        assert!(app.metadata.title.is_empty());
+        let new = dir.path().join("new.csv");
+        assert_eq!(app.file_path(), Some(new.as_path()));
+        app.handle_normal_key(KeyEvent::from(app.keys.undo));
+        assert_eq!(entry_labels(&app), ["keep", "#me"]);
+        assert_eq!(app.metadata.title, "Sortie 3");
+        assert_eq!(app.metadata.fields.len(), 1);
+        assert_eq!(app.metadata.created, created);
+        assert_eq!(app.file_path(), Some(old.as_path()));
+        app.handle_normal_key(KeyEvent::from(app.keys.redo));
+        assert!(app.entries.is_empty());
+        assert!(app.metadata.title.is_empty());
+        assert_eq!(app.file_path(), Some(new.as_path()));
+    }
+
+    #[test]
+    fn saving_after_undone_load_keeps_loaded_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let loaded = dir.path().join("b.csv");
+        app_with_entries(&["b1", "b2"])
+            .save_to_file(&loaded)
+            .unwrap();
+        let mine = dir.path().join("a.csv");
+        let mut app = app_with_entries(&["mine"]);
+        app.metadata.title = "Mine".into();
+        app.save_on_quit(&mine);
+        app.load_from_file_in_place(&loaded).unwrap();
+        assert!(!app.is_dirty());
+        assert!(app.undo());
+        assert_eq!(app.file_path(), Some(mine.as_path()));
+        assert_eq!(app.metadata.title, "Mine");
+        assert!(app.is_dirty());
+
+        app.start_save();
+        app.handle_editing_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(entry_labels(&App::load_from_file(&mine).unwrap()), ["mine"]);
+        assert_eq!(
+            entry_labels(&App::load_from_file(&loaded).unwrap()),
+            ["b1", "b2"]
+        );
+
+        assert!(app.redo());
+        assert_eq!(entry_labels(&app), ["b1", "b2"]);
+        assert_eq!(app.file_path(), Some(loaded.as_path()));
+        assert!(!app.is_dirty());
+    }
+
+    #[test]
+    fn undo_time_hack_restores_clock() {
+        let mut app = App::new();
+        app.input.push_str("01:02:03");
+        app.finalize_time_hack();
+        assert!(app.time_hack.is_some());
+        assert!(app.undo());
+        assert!(app.time_hack.is_none());
+        assert!(app.entries.is_empty());
+        assert!(app.redo());
+        assert!(app.time_hack.is_some());
+        assert_eq!(app.entries.len(), 1);
+
+        app.start_time_hack();
+        app.handle_editing_key(KeyEvent::from(KeyCode::Char('r')));
+        assert!(app.time_hack.is_none());
+        assert!(app.undo());
+        assert!(app.time_hack.is_some());
+    }
+
+    #[test]
+    fn undo_ignored_while_editing() {
+        let mut app = app_with_entries(&["a"]);
+        app.start_note();
+        assert!(!app.undo());
+        assert_eq!(app.entries.len(), 1);
+    }
+
+    #[test]
+    fn parse_timestamp_edits() {
+        let base = Local
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2024, 5, 6)
+                    .unwrap()
+                    .and_hms_milli_opt(12, 0, 0, 500)
+                    .unwrap(),
+            )
+            .unwrap();
+        let at = |h, m, s, ms| {
+            Local
+                .from_local_datetime(&base.date_naive().and_hms_milli_opt(h, m, s, ms).unwrap())
+                .unwrap()
+        };
+        assert_eq!(
+            parse_timestamp_edit("11:59:58.250", base, DisplayZone::Local),
+            Some(at(11, 59, 58, 250))
+        );
+        assert_eq!(
+            parse_timestamp_edit("13:00:01", base, DisplayZone::Local),
+            Some(at(13, 0, 1, 0))
+        );
+        assert_eq!(
+            parse_timestamp_edit("-1.5s", base, DisplayZone::Local),
+            Some(at(11, 59, 59, 0))
+        );
+        assert_eq!(
+            parse_timestamp_edit("+250ms", base, DisplayZone::Local),
+            Some(at(12, 0, 0, 750))
+        );
+        assert_eq!(
+            parse_timestamp_edit("+2m", base, DisplayZone::Local),
+            Some(at(12, 2, 0, 500))
+        );
+        assert_eq!(
+            parse_timestamp_edit("-1h", base, DisplayZone::Local),
+            Some(at(11, 0, 0, 500))
+        );
+        assert_eq!(
+            parse_timestamp_edit("+3", base, DisplayZone::Local),
+            Some(at(12, 0, 3, 500))
+        );
+        assert_eq!(parse_timestamp_edit("-abc", base, DisplayZone::Local), None);
+        assert_eq!(parse_timestamp_edit("noon", base, DisplayZone::Local), None);
+        let kolkata = "Asia/Kolkata".parse().unwrap();
+        let utc = base.with_timezone(&Utc).date_naive();
+        assert_eq!(
+            parse_timestamp_edit("05:30:00", base, DisplayZone::Utc),
+            Some(
+                Utc.from_utc_datetime(&utc.and_hms_opt(5, 30, 0).unwrap())
+                    .into()
+            )
+        );
+        assert_eq!(
+            parse_timestamp_edit("11:00:00", base, kolkata),
+            Some(
+                Utc.from_utc_datetime(&utc.and_hms_opt(5, 30, 0).unwrap())
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn edit_timestamp_keeps_original() {
+        let mut app = app_with_entries(&["a", "b"]);
+        let first = app.notes[0].timestamp;
+        let second = app.notes[1].timestamp;
+        app.selected = Some(1);
+        app.handle_normal_key(KeyEvent::from(app.keys.edit_time));
+        assert!(matches!(app.mode(), InputMode::EditingTimestamp));
+        app.input = "-10s".into();
+        app.handle_editing_key(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.mode(), InputMode::Normal));
+        assert_eq!(app.notes[1].timestamp, second - Duration::seconds(10));
+        assert_eq!(app.notes[1].original_timestamp, Some(second));
+        assert!(app.is_out_of_order(1));
+        assert!(!app.is_out_of_order(0));
+
+        app.edit_selected_time();
+        app.input = "+10s".into();
+        assert!(app.finalize_timestamp());
+        assert_eq!(app.notes[1].timestamp, second);
+        assert_eq!(app.notes[1].original_timestamp, Some(second));
+        assert!(!app.is_out_of_order(1));
+
+        assert!(app.undo());
+        assert!(app.undo());
+        assert_eq!(app.notes[1].original_timestamp, None);
+        assert_eq!(app.notes[0].timestamp, first);
+    }
+
+    #[test]
+    fn edit_timestamp_rejects_invalid_input() {
+        let mut app = app_with_entries(&["a"]);
+        app.edit_selected_time();
+        app.input = "later".into();
+        assert!(!app.finalize_timestamp());
+        assert!(matches!(app.mode(), InputMode::EditingTimestamp));
+        app.cancel_entry();
+        assert!(app.notes[0].original_timestamp.is_none());
+    }
+
+    #[test]
+    fn edit_timestamp_ignores_sections() {
+        let mut app = app_with_entries(&["#s"]);
+        app.edit_selected_time();
+        assert!(matches!(app.mode(), InputMode::Normal));
+    }
+
+    #[test]
+    fn original_timestamp_survives_save_and_load() {
+        let mut app = app_with_entries(&["a", "#s"]);
+        app.selected = Some(0);
+        app.edit_selected_time();
+        app.input = "+1s".into();
+        app.finalize_timestamp();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("retimed.csv");
+        app.save_to_file(&path).unwrap();
+        let loaded = App::load_from_file(&path).unwrap();
+        assert_eq!(loaded.entries, app.entries);
+    }
+
+    #[test]
+    fn metadata_survives_save_and_load() {
+        let mut app = app_with_entries(&["#intro", "a"]);
+        app.metadata.title = "Test point 4, run 2".into();
+        app.metadata.operator = "J. Doe".into();
+        app.metadata.set_field("aircraft", "N123AB");
+        app.metadata.set_field("notes", "line one, \"quoted\"");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("meta.csv");
+        app.save_to_file(&path).unwrap();
+        let loaded = App::load_from_file(&path).unwrap();
+        assert_eq!(loaded.entries, app.entries);
+        assert_eq!(loaded.metadata.title, app.metadata.title);
+        assert_eq!(loaded.metadata.operator, app.metadata.operator);
+        assert_eq!(loaded.metadata.created, app.metadata.created);
+        assert_eq!(loaded.metadata.fields, app.metadata.fields);
+        assert_eq!(loaded.metadata.time_source.as_deref(), Some("System"));
+    }
+
+    #[test]
+    fn every_file_format_round_trips() {
+        let mut app = app_with_entries(&["#intro", "a", "b"]);
+        app.metadata.title = "Formats".into();
+        let dir = tempfile::tempdir().unwrap();
+        for format in FileFormat::ALL {
+            let path = dir.path().join(format!("notes.{}", format.extension()));
+            app.save_to_file(&path).unwrap();
+            let loaded = App::load_from_file(&path).unwrap();
+            assert_eq!(loaded.entries, app.entries, "{format:?}");
+            assert_eq!(loaded.metadata.title, "Formats", "{format:?}");
+        }
+        let json = std::fs::read_to_string(dir.path().join("notes.json")).unwrap();
+        assert!(json.trim_start().starts_with('{'));
+    }
+
+    #[test]
+    fn headerless_file_loads_with_default_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.csv");
+        std::fs::write(
+            &path,
+            "section,,Intro\nnote,2024-01-01T10:00:00+00:00,hello\n",
+        )
+        .unwrap();
+        let loaded = App::load_from_file(&path).unwrap();
+        assert_eq!(entry_labels(&loaded), vec!["#Intro", "hello"]);
+        assert!(loaded.metadata.title.is_empty());
+        assert!(loaded.metadata.fields.is_empty());
+        assert!(loaded.metadata.time_source.is_none());
+    }
+
+    #[test]
+    fn entries_without_ids_get_stable_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.csv");
+        std::fs::write(
+            &path,
+            "note,2024-01-01T10:00:00+00:00,same\nnote,2024-01-01T10:00:00+00:00,same\n",
+        )
+        .unwrap();
+        let ids = |app: &App| app.entries.iter().map(Entry::id).collect::<Vec<_>>();
+        let first = App::load_from_file(&path).unwrap();
+        let again = App::load_from_file(&path).unwrap();
+        assert_eq!(ids(&first), ids(&again));
+        assert_ne!(first.entries[0].id(), first.entries[1].id());
+        assert!(!first.entries[0].id().is_nil());
+        assert_eq!(
+            first.entries[0].id().timestamp_ms(),
+            1_704_103_200_000,
+            "IDs of notes carry their timestamp"
+        );
+
+        // Once saved, the derived IDs are kept even if the rows move.
+        let saved = dir.path().join("new.jsonl");
+        first.save_to_file(&saved).unwrap();
+        let mut reloaded = App::load_from_file(&saved).unwrap();
+        reloaded.apply_change(Change::Move { from: 1, to: 0 });
+        reloaded.save_to_file(&saved).unwrap();
+        let mut expected = ids(&first);
+        expected.reverse();
+        assert_eq!(ids(&App::load_from_file(&saved).unwrap()), expected);
+    }
+
+    #[test]
+    fn metadata_overlay_edits_fields() {
+        let mut app = App::new();
+        let press = |app: &mut App, code: KeyCode| {
+            app.handle_event(&Event::Key(KeyEvent::from(code))).unwrap();
+        };
+        let type_text = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Char(c))))
+                    .unwrap();
+            }
+        };
+        app.start_metadata();
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode(), InputMode::EditingMetadata);
+        type_text(&mut app, "Sortie 7");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode(), InputMode::Metadata);
+        assert_eq!(app.metadata.title, "Sortie 7");
+
+        app.metadata_selected = app.metadata_rows() - 1;
+        press(&mut app, KeyCode::Enter);
+        type_text(&mut app, "tail = N123AB");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.metadata.field("tail"), Some("N123AB"));
+
+        app.metadata_selected = 2;
+        app.edit_selected_metadata();
+        assert_eq!(app.input(), "tail=N123AB");
+        app.input = "tail=".into();
+        app.finalize_metadata();
+        assert!(app.metadata.fields.is_empty());
+        assert_eq!(app.metadata_selected, 2);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.mode(), InputMode::Normal);
+    }
+
+    #[test]
+    fn unchanged_metadata_keeps_log_clean() {
+        let mut app = App::new();
+        app.metadata.title = "Sortie 7".into();
+        app.metadata.set_field("tail", "N123AB");
+        app.dirty = false;
+        for row in 0..app.metadata_rows() {
+            app.metadata_selected = row;
+            app.edit_selected_metadata();
+            app.finalize_metadata();
+        }
+        app.metadata_selected = app.metadata_rows() - 1;
+        app.edit_selected_metadata();
+        app.input = "no separator".into();
+        app.finalize_metadata();
+        assert!(!app.is_dirty());
+
+        app.metadata_selected = 0;
+        app.edit_selected_metadata();
+        app.input = "Sortie 8".into();
+        app.finalize_metadata();
+        assert!(app.is_dirty());
+    }
+
+    #[test]
+    fn save_failure_is_reported() {
+        let mut app = app_with_entries(&["a"]);
+        let dir = tempfile::tempdir().unwrap();
+        app.start_save();
+        app.input = dir
+            .path()
+            .join("missing")
+            .join("notes.csv")
+            .to_string_lossy()
+            .into();
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Enter)))
+            .unwrap();
+        let status = app.status().unwrap();
+        assert_eq!(status.kind, StatusKind::Error);
+        assert!(status.text.starts_with("Save failed: file not found"));
+
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Down)))
+            .unwrap();
+        assert!(app.status().is_none());
+    }
+
+    #[test]
+    fn save_success_is_reported() {
+        let mut app = app_with_entries(&["a", "b"]);
+        let dir = tempfile::tempdir().unwrap();
+        app.start_save();
+        app.input = dir.path().join("notes.csv").to_string_lossy().into();
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Enter)))
+            .unwrap();
+        let status = app.status().unwrap();
+        assert_eq!(status.kind, StatusKind::Info);
+        assert!(status.text.starts_with("Saved 2 entries"));
+    }
+
+    #[test]
+    fn load_failure_keeps_entries_and_reports_line() {
+        let mut app = app_with_entries(&["keep"]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.csv");
+        std::fs::write(&path, "note,2024-05-01T09:00:00+00:00,ok\nnote,soon,bad\n").unwrap();
+        app.load_files = vec![path];
+        app.load_selected = 0;
+        app.mode = InputMode::Loading;
+        app.handle_event(&Event::Key(KeyEvent::from(KeyCode::Enter)))
+            .unwrap();
+        assert_eq!(entry_labels(&app), vec!["keep"]);
+        let status = app.status().unwrap();
+        assert_eq!(status.kind, StatusKind::Error);
+        assert!(status.text.contains("broken.csv:2:"), "{}", status.text);
+    }
+
+    fn press(app: &mut App, key: impl Into<KeyCombo>) {
+        let event = KeyEvent::from(key.into());
+        app.handle_event(&Event::Key(event)).unwrap();
+    }
 
     #[test]
-    fn start_and_finalize_note() {
+    fn quit_without_changes_exits() {
         let mut app = App::new();
-        assert!(app.notes.is_empty());
-        app.start_note();
-        assert!(matches!(app.mode(), InputMode::EditingNote));
-        assert!(app.note_time.is_some());
-        app.input.push_str("test");
-        app.finalize_note();
-        assert!(app.note_time.is_none());
-        assert!(matches!(app.mode(), InputMode::Normal));
-        assert_eq!(app.notes.len(), 1);
-        assert_eq!(app.notes[0].text, "test");
+        app.keys.quit = KeyCode::Char('q').into();
+        assert!(!app.is_dirty());
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.should_quit());
     }
 
     #[test]
-    fn cancel_entry() {
+    fn quit_with_unsaved_changes_asks_first() {
         let mut app = App::new();
-        app.start_note();
-        app.input.push_str("discard");
-        app.cancel_entry();
-        assert!(app.notes.is_empty());
-        assert!(app.input.is_empty());
-        assert!(app.note_time.is_none());
-        assert!(matches!(app.mode(), InputMode::Normal));
+        app.keys.quit = KeyCode::Char('q').into();
+        app.mark_time();
+        assert!(app.is_dirty());
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.mode(), InputMode::ConfirmQuit);
+        assert!(!app.should_quit());
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.mode(), InputMode::Normal);
+        assert!(!app.should_quit());
+
+        press(&mut app, KeyCode::Char('q'));
+        app.quit_selected = 1;
+        press(&mut app, KeyCode::Enter);
+        assert!(app.should_quit());
     }
 
     #[test]
-    fn save_and_load() {
-        let mut app = App::new();
-        app.start_note();
-        app.input.push_str("hello");
-        app.finalize_note();
-        app.start_section();
-        app.input.push_str("section");
-        app.finalize_section();
+    fn save_and_quit_prompts_for_file() {
+        let mut app = app_with_entries(&["a"]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quit.csv");
+        app.request_quit();
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode(), InputMode::Saving);
+        app.input = path.to_string_lossy().into();
+        press(&mut app, KeyCode::Enter);
+        assert!(app.should_quit());
+        assert!(!app.is_dirty());
+        assert_eq!(app.file_path(), Some(path.as_path()));
+        assert_eq!(App::load_from_file(&path).unwrap().entries, app.entries);
+    }
 
+    #[test]
+    fn cancelled_save_prompt_does_not_quit() {
+        let mut app = app_with_entries(&["a"]);
         let dir = tempfile::tempdir().unwrap();
-        let path = dir.path().join("notes.csv");
-        app.save_to_file(&path).unwrap();
+        app.request_quit();
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Esc);
+        app.start_save();
+        app.input = dir.path().join("later.csv").to_string_lossy().into();
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.is_dirty());
+        assert!(!app.should_quit());
+    }
 
-        let loaded = App::load_from_file(&path).unwrap();
-        assert_eq!(loaded.entries, app.entries);
-        assert_eq!(loaded.notes, app.notes);
+    #[test]
+    fn save_on_quit_writes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("auto.csv");
+        let mut app = app_with_entries(&["a"]);
+        app.save_on_quit(&path);
+        app.request_quit();
+        assert!(app.should_quit());
+        assert_eq!(App::load_from_file(&path).unwrap().entries, app.entries);
     }
 
     #[test]
-    fn selection_navigation() {
-        let mut app = App::new();
-        app.start_note();
-        app.finalize_note();
-        app.start_section();
-        app.input.push('a');
-        app.finalize_section();
+    fn save_merges_entries_appended_by_other_programs() {
+        let dir = tempfile::tempdir().unwrap();
+        for format in FileFormat::ALL {
+            let path = dir.path().join(format!("shared.{}", format.extension()));
+            App::append_to_file(&path, Entry::Note(Note::new(Local::now(), "first"))).unwrap();
+            let mut app = App::load_from_file(&path).unwrap();
+            app.start_note();
+            app.input = "mine".into();
+            app.finalize_note();
+            App::append_to_file(&path, Entry::Note(Note::new(Local::now(), "theirs"))).unwrap();
 
-        assert_eq!(app.selected(), Some(1));
-        app.select_previous();
-        assert_eq!(app.selected(), Some(0));
-        app.select_previous();
-        assert_eq!(app.selected(), Some(0));
-        app.select_next();
-        assert_eq!(app.selected(), Some(1));
-        app.select_next();
-        assert_eq!(app.selected(), Some(1));
+            app.save_on_quit(&path);
+            app.request_quit();
+            assert!(app.should_quit(), "{format:?}");
+            assert_eq!(
+                entry_labels(&app),
+                ["first", "mine", "theirs"],
+                "{format:?}"
+            );
+            assert!(app.status().unwrap().text.contains("1 added"), "{format:?}");
+            let saved = App::load_from_file(&path).unwrap();
+            assert_eq!(saved.entries, app.entries, "{format:?}");
+        }
     }
 
     #[test]
-    fn edit_existing_note() {
-        let mut app = App::new();
-        app.start_note();
-        app.input.push_str("first");
-        app.finalize_note();
+    fn save_merges_by_id_after_other_session_rewrote_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.csv");
+        app_with_entries(&["a", "b"]).save_to_file(&path).unwrap();
+        let mut mine = App::load_from_file(&path).unwrap();
+        let mut theirs = App::load_from_file(&path).unwrap();
 
-        app.start_note();
-        app.input.push_str("second");
-        app.finalize_note();
+        theirs.selected = Some(0);
+        theirs.edit_selected();
+        theirs.input = "a2".into();
+        theirs.finalize_note();
+        theirs.apply_change(Change::Insert {
+            index: 0,
+            entry: Entry::Note(Note::new(Local::now(), "x")),
+        });
+        theirs.save_with_status(&path);
 
-        app.selected = Some(0);
-        app.edit_selected();
-        assert!(matches!(app.mode(), InputMode::EditingExistingNote));
-        app.input.clear();
-        app.input.push_str("updated");
-        app.finalize_note();
-        assert_eq!(app.notes[0].text, "updated");
+        mine.apply_change(Change::Remove { index: 1 });
+        mine.save_with_status(&path);
+        assert_eq!(entry_labels(&mine), ["a", "x"]);
+        let saved = App::load_from_file(&path).unwrap();
+        assert_eq!(saved.entries, mine.entries);
     }
 
     #[test]
-    fn edit_existing_section() {
-        let mut app = App::new();
-        app.start_section();
-        app.input.push_str("sec");
-        app.finalize_section();
-        app.selected = Some(0);
+    fn edit_follows_entry_when_others_are_inserted() {
+        let mut app = app_with_entries(&["a", "b"]);
+        app.selected = Some(1);
         app.edit_selected();
-        assert!(matches!(app.mode(), InputMode::EditingExistingSection));
-        app.input.clear();
-        app.input.push_str("new");
-        app.finalize_section();
-        if let Entry::Section(sec) = &app.entries[0] {
-            assert_eq!(sec.title, "new");
-        } else {
-            panic!("Expected section");
-        }
+        app.input = "b2".into();
+        app.apply_change(Change::Insert {
+            index: 0,
+            entry: Entry::Section(Section::new("remote")),
+        });
+        app.finalize_note();
+        assert_eq!(entry_labels(&app), ["#remote", "a", "b2"]);
     }
 
     #[test]
-    fn load_in_place() {
-        let mut app = App::new();
-        app.start_note();
-        app.input.push_str("data");
-        app.finalize_note();
+    fn append_refuses_unreadable_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("future.csv");
+        std::fs::write(&path, "#rtnt,99\n").unwrap();
+        let entry = Entry::Section(Section::new("x"));
+        assert!(matches!(
+            App::append_to_file(&path, entry),
+            Err(AppError::UnsupportedVersion { found: 99, .. })
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "#rtnt,99\n");
+    }
 
+    #[test]
+    fn append_rewrites_older_csv_layouts() {
         let dir = tempfile::tempdir().unwrap();
-        let path = dir.path().join("file.csv");
-        app.save_to_file(&path).unwrap();
+        let headerless = "section,,Takeoff\nnote,2024-05-01T09:00:00+00:00,Brakes released\n";
+        let reordered = "#rtnt,1\nid,text,type,timestamp,pending\n,Takeoff,section,,\n";
+        for (name, contents) in [("v0.csv", headerless), ("reordered.csv", reordered)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            let mut note = Note::new(Local::now(), "");
+            note.source = Some("udp".into());
+            let id = note.id;
+            App::append_to_file(&path, Entry::Note(note)).unwrap();
 
-        let mut other = App::new();
-        other.load_from_file_in_place(&path).unwrap();
-        assert_eq!(other.entries.len(), 1);
-        if let Entry::Note(n) = &other.entries[0] {
-            assert_eq!(n.text, "data");
-        } else {
-            panic!("Expected note");
+            assert!(std::fs::read_to_string(&path)
+                .unwrap()
+                .starts_with("#rtnt,1\ntype,timestamp,"));
+            let app = App::load_from_file(&path).unwrap();
+            assert_eq!(entry_labels(&app)[0], "#Takeoff", "{name}");
+            let Some(Entry::Note(n)) = app.entries.last() else {
+                panic!("{name}: last entry is not a note");
+            };
+            assert!(!n.pending, "{name}");
+            assert_eq!(n.id, id, "{name}");
+            assert_eq!(n.source.as_deref(), Some("udp"), "{name}");
         }
     }
 
     #[test]
-    fn handle_event_note_flow() {
-        let mut app = App::new();
-        let enter = Event::Key(KeyEvent::from(KeyCode::Enter));
-        app.handle_event(&enter).unwrap();
+    fn remote_commands_do_not_disturb_typing() {
+        let mut app = app_with_entries(&["a"]);
+        app.start_note();
+        app.input = "typing".into();
+        let event = |command| RemoteEvent {
+            command,
+            source: "sim".into(),
+            received: Instant::now(),
+        };
+        app.apply_remote(event(RemoteCommand::Note {
+            text: "Gear up".into(),
+            at: None,
+        }));
+        app.apply_remote(event(RemoteCommand::Section {
+            title: "Approach".into(),
+        }));
+        app.apply_remote(event(RemoteCommand::Mark { at: None }));
         assert!(matches!(app.mode(), InputMode::EditingNote));
-        let a = Event::Key(KeyEvent::from(KeyCode::Char('a')));
-        let b = Event::Key(KeyEvent::from(KeyCode::Char('b')));
-        app.handle_event(&a).unwrap();
-        app.handle_event(&b).unwrap();
-        app.handle_event(&enter).unwrap();
-        assert_eq!(app.notes.len(), 1);
-        assert_eq!(app.notes[0].text, "ab");
+        assert_eq!(app.input(), "typing");
+        assert_eq!(entry_labels(&app), ["a", "Gear up", "#Approach", ""]);
+        assert_eq!(app.pending_count(), 1);
+        assert!(app
+            .notes
+            .iter()
+            .skip(1)
+            .all(|n| n.source.as_deref() == Some("sim")));
+        assert!(matches!(&app.entries[2], Entry::Section(s) if s.source.as_deref() == Some("sim")));
+
+        // The typed note was started first, so it goes before the later mark.
+        app.finalize_note();
+        assert_eq!(
+            entry_labels(&app),
+            ["a", "Gear up", "#Approach", "typing", ""]
+        );
+        assert_eq!(app.notes[2].source, None);
     }
 
     #[test]
-    fn handle_left_right_navigation() {
+    fn udp_datagrams_mark_time() {
         let mut app = App::new();
-        let enter = Event::Key(KeyEvent::from(KeyCode::Enter));
-        app.handle_event(&enter).unwrap();
-        let a = Event::Key(KeyEvent::from(KeyCode::Char('a')));
-        let b = Event::Key(KeyEvent::from(KeyCode::Char('b')));
-        let c = Event::Key(KeyEvent::from(KeyCode::Char('c')));
-        app.handle_event(&a).unwrap();
-        app.handle_event(&b).unwrap();
-        app.handle_event(&c).unwrap();
-        let left = Event::Key(KeyEvent::from(KeyCode::Left));
-        app.handle_event(&left).unwrap();
-        app.handle_event(&left).unwrap();
-        let x = Event::Key(KeyEvent::from(KeyCode::Char('X')));
-        app.handle_event(&x).unwrap();
-        let right = Event::Key(KeyEvent::from(KeyCode::Right));
-        app.handle_event(&right).unwrap();
-        app.handle_event(&enter).unwrap();
-        assert_eq!(app.notes[0].text, "aXbc");
+        let addr = app.listen_udp("127.0.0.1:0").unwrap();
+        let client = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.send_to(b"", addr).unwrap();
+        client.send_to(b"Event button", addr).unwrap();
+        let start = Instant::now();
+        while app.entries.len() < 2 && start.elapsed() < std::time::Duration::from_secs(5) {
+            app.process_remote();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(entry_labels(&app), ["", "Event button"]);
+        assert_eq!(app.pending_count(), 1);
+        assert!(app.notes.iter().all(|n| n.source.as_deref() == Some("udp")));
     }
 
     #[test]
-    fn change_keybinding() {
-        let mut keys = KeyBindings::default();
-        keys.set(Action::Save, KeyCode::Char('z'));
-        keys.save();
-        let loaded = KeyBindings::load_or_default();
-        assert_eq!(loaded.get(Action::Save), KeyCode::Char('z'));
-        let _ = std::fs::remove_file(KeyBindings::config_path_for_test());
+    fn remote_time_hack_uses_arrival_time() {
+        let mut app = App::new();
+        let received = Instant::now()
+            .checked_sub(std::time::Duration::from_secs(2))
+            .unwrap();
+        app.apply_remote(RemoteEvent {
+            command: RemoteCommand::TimeHack {
+                time: "12:00:00".parse().unwrap(),
+            },
+            source: "gps".into(),
+            received,
+        });
+        assert_eq!(app.time_source(), "Hacked");
+        let now = app.current_time().time();
+        assert!(now >= NaiveTime::from_hms_opt(12, 0, 2).unwrap(), "{now}");
+        assert!(now < NaiveTime::from_hms_opt(12, 0, 3).unwrap(), "{now}");
+        assert_eq!(app.notes[0].source.as_deref(), Some("gps"));
     }
 
     #[test]
-    fn rebind_conflict() {
-        let mut app = App::new();
-        app.start_keybindings();
-        app.capture_action = Some(Action::Load);
-        // Press key already bound to Save
-        let key = KeyEvent::from(KeyCode::Char('w'));
-        app.handle_capture_key(key);
-        assert!(matches!(app.mode(), InputMode::ConfirmReplace));
-        assert_eq!(app.pending_action, Some(Action::Load));
-        assert_eq!(app.pending_conflict, Some(Action::Save));
-        // Confirm replacement
-        app.handle_confirm_key(KeyEvent::from(KeyCode::Enter));
-        assert_eq!(app.keys.get(Action::Load), KeyCode::Char('w'));
-        assert_eq!(app.keys.get(Action::Save), KeyCode::Null);
+    fn stream_reports_edits_by_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let mut app = app_with_entries(&["a"]);
+        app.stream_to(&path).unwrap();
+        let app_id = app.entries[0].id();
+        app.selected = Some(0);
+        app.edit_selected();
+        app.input = "b".into();
+        app.finalize_note();
+        app.input = "12:00:00".into();
+        app.finalize_time_hack();
+        app.undo();
+        drop(app);
+
+        let events: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|e| e["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "reset",
+                "update",
+                "insert",
+                "time_hack",
+                "remove",
+                "time_hack"
+            ]
+        );
+        assert_eq!(events[1]["id"], events[0]["entries"][0]["Note"]["id"]);
+        assert_eq!(events[1]["id"], app_id.to_string());
+        assert_eq!(events[1]["entry"]["Note"]["text"], "b");
+        let reference = events[3]["reference"].as_str().unwrap();
+        let reference = DateTime::parse_from_rfc3339(reference).unwrap();
+        assert_eq!(reference.offset().local_minus_utc(), 0);
+        assert_eq!(
+            reference
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string(),
+            "12:00:00"
+        );
+        assert_eq!(events[4]["id"], events[2]["id"]);
+        assert!(events[5]["reference"].is_null());
     }
 
     #[test]
-    fn rebind_bindings_key_warning() {
+    fn quit_key_is_typed_into_notes() {
         let mut app = App::new();
-        app.start_keybindings();
-        app.capture_action = Some(Action::Save);
-        let key = KeyEvent::from(app.keys.bindings);
-        app.handle_capture_key(key);
-        assert!(matches!(app.mode(), InputMode::BindWarning));
-        assert_eq!(app.capture_action, Some(Action::Save));
-        app.handle_bind_warning_key(KeyEvent::from(KeyCode::Char('x')));
-        assert!(matches!(app.mode(), InputMode::KeyCapture));
-        assert_eq!(app.capture_action, Some(Action::Save));
+        app.keys.quit = KeyCode::Char('q').into();
+        app.start_note();
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.input(), "q");
+        assert!(!app.should_quit());
     }
 
     #[test]
-    fn time_hack_sets_clock() {
+    fn quit_while_typing_keeps_note() {
+        let quit = KeyCombo::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
         let mut app = App::new();
-        app.time_hack = Some((NaiveTime::from_hms_opt(1, 2, 3).unwrap(), Instant::now()));
+        app.keys.quit = quit;
         app.start_note();
-        let base_dt = Local
-            .from_local_datetime(
-                &Local::now()
-                    .date_naive()
-                    .and_time(NaiveTime::from_hms_opt(1, 2, 3).unwrap()),
-            )
-            .unwrap();
-        let diff = app.note_time.unwrap() - base_dt;
-        assert!(diff.num_seconds() >= 0 && diff.num_seconds() < 1);
+        app.input = "engine start".into();
+        press(&mut app, quit);
+        assert_eq!(entry_labels(&app), vec!["engine start"]);
+        assert_eq!(app.mode(), InputMode::ConfirmQuit);
+
+        let mut app = App::new();
+        app.keys.quit = quit;
+        app.start_note();
+        let time = app.note_time().unwrap();
+        press(&mut app, quit);
+        assert!(matches!(&app.entries[..], [Entry::Note(n)] if n.pending && n.timestamp == time));
     }
 
     #[test]
-    fn finalize_time_hack_adds_entry() {
+    fn file_menu_exports_markdown() {
+        let mut app = app_with_entries(&["#Climb", "level off"]);
+        let dir = tempfile::tempdir().unwrap();
+        app.save_dir = dir.path().to_path_buf();
+        app.start_file_menu();
+        app.file_menu_selected = 3;
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode(), InputMode::Exporting);
+        let path = dir.path().join("notes.md");
+        assert_eq!(app.input(), path.to_string_lossy());
+        press(&mut app, KeyCode::Enter);
+        let md = std::fs::read_to_string(&path).unwrap();
+        assert!(md.contains("## Climb"));
+        assert!(md.contains("level off"));
+        assert_eq!(app.status().unwrap().kind, StatusKind::Info);
+    }
+
+    #[test]
+    fn file_menu_export_names_recorded_time_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("gps.csv");
+        let mut gps = app_with_entries(&["level off"]);
+        gps.metadata.time_source = Some("GPS".into());
+        gps.save_to_file(&log).unwrap();
         let mut app = App::new();
-        app.input.push_str("01:02:03");
-        app.finalize_time_hack();
-        assert!(app.time_hack.is_some());
-        assert_eq!(app.entries.len(), 1);
-        if let Entry::Note(n) = &app.entries[0] {
-            assert!(n.text.contains("Time Hack"));
-        } else {
-            panic!("Expected note");
-        }
+        app.load_from_file_in_place(&log).unwrap();
+        app.start_file_menu();
+        app.file_menu_selected = 3;
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Enter);
+        let md = std::fs::read_to_string(dir.path().join("gps.md")).unwrap();
+        assert!(md.contains("**Time source:** GPS"));
     }
+
     #[test]
-    fn finalize_time_hack_invalid() {
+    fn modifier_binding_does_not_steal_plain_key() {
         let mut app = App::new();
-        app.input.push_str("bad");
-        app.finalize_time_hack();
-        assert!(app.time_hack.is_none());
-        assert!(app.entries.is_empty());
-        assert!(app.input.is_empty());
+        app.keys.save = KeyCombo::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        app.handle_normal_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert!(matches!(app.mode(), InputMode::Saving));
+        app.cancel_entry();
+        app.handle_normal_key(KeyEvent::from(KeyCode::Char('s')));
+        assert!(matches!(app.mode(), InputMode::EditingSection));
+        app.cancel_entry();
+        app.handle_normal_key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL));
         assert!(matches!(app.mode(), InputMode::Normal));
     }
 
     #[test]
-    fn start_load_filters_csv() {
-        let dir = tempfile::tempdir().unwrap();
-        let csv = dir.path().join("a.csv");
-        let txt = dir.path().join("b.txt");
-        std::fs::write(&csv, "").unwrap();
-        std::fs::write(&txt, "").unwrap();
+    fn modifier_mark_works_while_typing() {
         let mut app = App::new();
-        app.save_dir = dir.path().to_path_buf();
-        app.start_load();
-        assert!(matches!(app.mode(), InputMode::Loading));
-        assert_eq!(app.load_files, vec![csv]);
+        app.keys.mark = KeyCombo::new(KeyCode::Char('m'), KeyModifiers::ALT);
+        app.start_note();
+        app.handle_editing_key(KeyEvent::from(KeyCode::Char('m')));
+        app.handle_editing_key(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT));
+        assert_eq!(app.input(), "m");
+        assert_eq!(app.pending_count(), 1);
     }
 
     #[test]
-    fn keybinding_action_lookup() {
-        let bindings = KeyBindings::default();
-        for a in Action::ALL {
-            assert_eq!(bindings.action_for_key(bindings.get(a)), Some(a));
-        }
-        assert_eq!(bindings.action_for_key(KeyCode::F(1)), None);
+    fn capture_records_modifiers() {
+        let mut app = App::new();
+        app.start_keybindings();
+        app.capture_action = Some(Action::NewSection);
+        app.handle_capture_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT));
+        assert!(matches!(app.mode(), InputMode::KeyBindings));
+        assert_eq!(
+            app.keys.get(Action::NewSection),
+            KeyCombo::new(KeyCode::Enter, KeyModifiers::ALT)
+        );
+        assert_eq!(app.keys.get(Action::NewNote), KeyCode::Enter);
+        let _ = std::fs::remove_file(KeyBindings::config_path_for_test());
     }
 
     #[test]
-    fn time_source_changes() {
-        let mut app = App::new();
-        assert_eq!(app.time_source(), "System");
-        app.time_hack = Some((NaiveTime::from_hms_opt(0, 0, 0).unwrap(), Instant::now()));
-        assert_eq!(app.time_source(), "Hacked");
+    fn keybindings_config_keeps_modifiers() {
+        let mut keys = KeyBindings::default();
+        keys.set(
+            Action::Save,
+            KeyCombo::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+        );
+        let cfg = KeyBindingsConfig::from(keys.clone());
+        assert_eq!(cfg.save, "Ctrl+s");
+        let restored = KeyBindings::from(cfg);
+        assert_eq!(restored.get(Action::Save), keys.get(Action::Save));
+    }
+
+    #[test]
+    fn keybindings_config_keeps_named_keys() {
+        let mut keys = KeyBindings::default();
+        keys.set(Action::Bindings, KeyCode::F(1));
+        keys.set(Action::Mark, KeyCode::Char(' '));
+        keys.set(Action::NewSection, KeyCode::PageDown);
+        let cfg = KeyBindingsConfig::from(keys.clone());
+        assert_eq!(cfg.bindings, "F1");
+        assert_eq!(cfg.mark, "Space");
+        let restored = KeyBindings::from(cfg);
+        for action in Action::ALL {
+            assert_eq!(restored.get(action), keys.get(action), "{action:?}");
+        }
     }
 
     #[test]
@@ -0,0 +1,640 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeZone, Utc,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::zone::DisplayZone;
+
+/// A source of the current time for stamping notes.
+///
+/// [`App`](crate::App) reads every timestamp from its clock source, so notes
+/// can be stamped from a reference such as a GPS receiver instead of the
+/// system clock. A manual [`TimeHack`] is applied on top of whichever source
+/// is active.
+pub trait ClockSource: fmt::Debug {
+    /// Short name shown in the status line and saved with the session.
+    fn name(&self) -> &str;
+
+    /// Returns the current time according to this source.
+    fn now(&self) -> DateTime<Local>;
+
+    /// Reports whether the readings of this source can be trusted.
+    fn health(&self) -> ClockHealth {
+        ClockHealth::Good
+    }
+}
+
+/// Condition of a [`ClockSource`] as shown in the status line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClockHealth {
+    /// The source is delivering time normally.
+    Good,
+    /// The source has stopped delivering time and readings come from a
+    /// fallback; the message says why.
+    Stale(String),
+}
+
+impl ClockHealth {
+    /// Returns `true` if the source is delivering time normally.
+    #[must_use]
+    pub fn is_good(&self) -> bool {
+        *self == Self::Good
+    }
+}
+
+impl fmt::Display for ClockHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Good => f.write_str("OK"),
+            Self::Stale(reason) => write!(f, "stale: {reason}"),
+        }
+    }
+}
+
+/// The local system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl ClockSource for SystemClock {
+    fn name(&self) -> &'static str {
+        "System"
+    }
+
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// A clock that only moves when told to, for tests and replaying sessions.
+///
+/// Clones share the same time, so a handle kept after giving the clock to an
+/// [`App`](crate::App) still controls it.
+///
+/// # Example
+/// ```
+/// use chrono::{Duration, Local, TimeZone};
+/// use real_time_note_taker::{App, SimulatedClock};
+///
+/// let clock = SimulatedClock::new(Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap());
+/// let mut app = App::new();
+/// app.set_clock(clock.clone());
+/// clock.advance(Duration::milliseconds(1500));
+/// assert_eq!(app.current_time().format("%H:%M:%S%.3f").to_string(), "09:00:01.500");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    now: Arc<Mutex<DateTime<Local>>>,
+}
+
+impl SimulatedClock {
+    /// Creates a clock reading `start`.
+    #[must_use]
+    pub fn new(start: DateTime<Local>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    /// Sets the clock to `time`.
+    pub fn set(&self, time: DateTime<Local>) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = time;
+    }
+
+    /// Moves the clock forward by `by`, or back if it is negative.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl ClockSource for SimulatedClock {
+    fn name(&self) -> &'static str {
+        "Simulated"
+    }
+
+    fn now(&self) -> DateTime<Local> {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A manual correction of the clock, entered by the operator or sent by
+/// another program.
+///
+/// The hack is stored as the offset of the reference from the active
+/// [`ClockSource`], so later readings advance at the rate of the source and
+/// a reference on another day or in another zone is kept exactly. In saved
+/// files it is written as the `reference` and `clock` times it was taken at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "HackRecord", into = "HackRecord")]
+pub struct TimeHack {
+    /// Reference time minus the clock source time.
+    pub offset: Duration,
+    /// Reading of the clock source at the moment of the hack.
+    pub set_at: DateTime<Local>,
+}
+
+impl TimeHack {
+    /// Creates a hack recording that the reference read `reference` when the
+    /// clock source read `set_at`.
+    #[must_use]
+    pub fn new(reference: DateTime<Local>, set_at: DateTime<Local>) -> Self {
+        Self {
+            offset: reference - set_at,
+            set_at,
+        }
+    }
+
+    /// Returns the reference time at the moment of the hack.
+    #[must_use]
+    pub fn reference(&self) -> DateTime<Local> {
+        self.set_at + self.offset
+    }
+
+    /// Converts a reading of the clock source into reference time.
+    #[must_use]
+    pub fn apply(&self, now: DateTime<Local>) -> DateTime<Local> {
+        now + self.offset
+    }
+}
+
+/// Serialized form of a [`TimeHack`].
+#[derive(Serialize, Deserialize)]
+struct HackRecord {
+    #[serde(with = "crate::zone::utc")]
+    reference: DateTime<Local>,
+    #[serde(with = "crate::zone::utc")]
+    clock: DateTime<Local>,
+}
+
+impl From<HackRecord> for TimeHack {
+    fn from(record: HackRecord) -> Self {
+        Self::new(record.reference, record.clock)
+    }
+}
+
+impl From<TimeHack> for HackRecord {
+    fn from(hack: TimeHack) -> Self {
+        Self {
+            reference: hack.reference(),
+            clock: hack.set_at,
+        }
+    }
+}
+
+/// Estimates how fast the clock source drifts from the reference, from a
+/// series of time hacks against the same reference.
+///
+/// The estimate is the slope of a least squares line through the offsets of
+/// the hacks over clock source time.
+///
+/// # Returns
+/// The change in offset per second of clock source time, positive when the
+/// source runs slow, or `None` with fewer than two hacks at distinct times.
+///
+/// # Example
+/// ```
+/// use chrono::{Duration, Local, TimeZone};
+/// use real_time_note_taker::{clock_drift, TimeHack};
+///
+/// let start = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+/// let later = start + Duration::hours(1);
+/// let hacks = [
+///     TimeHack::new(start, start),
+///     TimeHack::new(later + Duration::milliseconds(36), later),
+/// ];
+/// let rate = clock_drift(&hacks).unwrap();
+/// assert!((rate * 1e6 - 10.0).abs() < 1e-6, "10 ppm");
+/// ```
+#[must_use]
+pub fn clock_drift(hacks: &[TimeHack]) -> Option<f64> {
+    let first = hacks.first()?.set_at;
+    let points: Vec<(f64, f64)> = hacks
+        .iter()
+        .map(|h| (seconds(h.set_at - first), seconds(h.offset)))
+        .collect();
+    #[allow(clippy::cast_precision_loss)]
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let spread: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if spread == 0.0 {
+        return None;
+    }
+    let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    Some(covariance / spread)
+}
+
+/// Corrects a timestamp taken while the earlier of two consecutive hacks was
+/// active, by interpolating the offset linearly between them.
+///
+/// `hacks` must be in the order they were taken. A timestamp taken before the
+/// first hack or after the last one is not between hacks and gives `None`.
+#[must_use]
+pub fn interpolate_hacks(hacks: &[TimeHack], stamped: DateTime<Local>) -> Option<DateTime<Local>> {
+    hacks.windows(2).find_map(|pair| {
+        let (from, to) = (pair[0], pair[1]);
+        let span = to.set_at - from.set_at;
+        if span <= Duration::zero()
+            || stamped < from.reference()
+            || stamped >= from.apply(to.set_at)
+        {
+            return None;
+        }
+        let clock = stamped - from.offset;
+        let fraction = seconds(clock - from.set_at) / seconds(span);
+        #[allow(clippy::cast_possible_truncation)]
+        let change = (seconds(to.offset - from.offset) * fraction * 1e6).round() as i64;
+        Some(clock + from.offset + Duration::microseconds(change))
+    })
+}
+
+/// Converts a duration to fractional seconds.
+#[allow(clippy::cast_precision_loss)]
+fn seconds(duration: Duration) -> f64 {
+    duration
+        .num_microseconds()
+        .map_or(duration.num_milliseconds() as f64 / 1e3, |us| {
+            us as f64 / 1e6
+        })
+}
+
+/// A reference time as entered for a time hack, before its date is known.
+///
+/// Accepted forms are:
+///
+/// - an ISO 8601 date and time such as `2024-05-01T13:45:00.250Z`, with an
+///   optional `Z` or `+HH:MM` suffix and local time if there is none
+/// - a time of day `HH:MM:SS[.fff]`, with the same optional suffix
+/// - an IRIG style day of year and UTC time `DDD:HH:MM:SS[.fff]`
+///
+/// Times without a date are placed on the day, or in the year, that puts
+/// them closest to the current time, so a hack taken around midnight or
+/// against a reference whose date differs from the local one is not a day
+/// off.
+///
+/// # Example
+/// ```
+/// use chrono::{Local, TimeZone, Utc};
+/// use real_time_note_taker::HackTime;
+///
+/// let now = Utc.with_ymd_and_hms(2024, 5, 1, 23, 59, 0).unwrap().with_timezone(&Local);
+/// let hack: HackTime = "00:00:30Z".parse().unwrap();
+/// let expected = Utc.with_ymd_and_hms(2024, 5, 2, 0, 0, 30).unwrap();
+/// assert_eq!(hack.resolve(now), Some(expected.with_timezone(&Local)));
+///
+/// let irig: HackTime = "122:23:59:30".parse().unwrap();
+/// let expected = Utc.with_ymd_and_hms(2024, 5, 1, 23, 59, 30).unwrap();
+/// assert_eq!(irig.resolve(now), Some(expected.with_timezone(&Local)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum HackTime {
+    /// A date and time in a known zone.
+    At(DateTime<FixedOffset>),
+    /// A date and time in the local zone.
+    Local(NaiveDateTime),
+    /// A time of day in the given zone, or the local zone if `None`.
+    TimeOfDay(NaiveTime, Option<FixedOffset>),
+    /// A day of the year, starting at 1, and time of day in UTC.
+    DayOfYear(u32, NaiveTime),
+}
+
+impl HackTime {
+    /// Returns the reference time this stands for, taking the missing date
+    /// parts from whichever candidate is nearest to `now`.
+    ///
+    /// # Returns
+    /// `None` if the time does not exist, for example a day of year beyond
+    /// the end of the year or a local time skipped by a daylight saving
+    /// change.
+    #[must_use]
+    pub fn resolve(self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.resolve_in(now, DisplayZone::Local)
+    }
+
+    /// Like [`resolve`](Self::resolve), but reads times without a zone as
+    /// times in `zone` instead of local times.
+    #[must_use]
+    pub fn resolve_in(self, now: DateTime<Local>, zone: DisplayZone) -> Option<DateTime<Local>> {
+        let nearest = |candidates: [Option<DateTime<Local>>; 3]| {
+            candidates
+                .into_iter()
+                .flatten()
+                .min_by_key(|t| (*t - now).abs())
+        };
+        match self {
+            Self::At(time) => Some(time.with_timezone(&Local)),
+            Self::Local(time) => zone.from_wall_clock(time),
+            Self::TimeOfDay(time, Some(offset)) => {
+                let today = now.with_timezone(&offset).date_naive();
+                nearest([-1, 0, 1].map(|days| {
+                    let date = today.checked_add_signed(Duration::days(days))?;
+                    single(offset.from_local_datetime(&date.and_time(time)))
+                        .map(|t| t.with_timezone(&Local))
+                }))
+            }
+            Self::TimeOfDay(time, None) => {
+                let today = zone.wall_clock(now).date();
+                nearest([-1, 0, 1].map(|days| {
+                    let date = today.checked_add_signed(Duration::days(days))?;
+                    zone.from_wall_clock(date.and_time(time))
+                }))
+            }
+            Self::DayOfYear(day, time) => {
+                let year = now.with_timezone(&Utc).year();
+                nearest([-1, 0, 1].map(|years| {
+                    let date = NaiveDate::from_yo_opt(year + years, day)?;
+                    Some(date.and_time(time).and_utc().with_timezone(&Local))
+                }))
+            }
+        }
+    }
+}
+
+/// Returns the only, or earlier, time a local time maps to.
+fn single<Tz: TimeZone>(result: LocalResult<DateTime<Tz>>) -> Option<DateTime<Tz>> {
+    match result {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => Some(t),
+        LocalResult::None => None,
+    }
+}
+
+/// Parses `HH:MM:SS` with optional fractional seconds.
+fn parse_time_of_day(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+        .ok()
+}
+
+/// Splits a trailing `Z`, `+HH:MM`, `+HHMM` or `+HH` zone suffix off `s`.
+fn split_offset(s: &str) -> Option<(&str, Option<FixedOffset>)> {
+    if let Some(rest) = s.strip_suffix(['Z', 'z']) {
+        return Some((rest, FixedOffset::east_opt(0)));
+    }
+    // Only look after the first `:` so the dashes of a date are skipped.
+    let time_start = s.find(':').unwrap_or(0);
+    let Some(at) = s[time_start..].rfind(['+', '-']).map(|i| i + time_start) else {
+        return Some((s, None));
+    };
+    let (rest, suffix) = s.split_at(at);
+    let digits = suffix[1..].replace(':', "");
+    if digits.len() != 2 && digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = if digits.len() == 4 {
+        digits[2..].parse().ok()?
+    } else {
+        0
+    };
+    let seconds = (hours * 60 + minutes) * 60;
+    let offset = if suffix.starts_with('-') {
+        FixedOffset::west_opt(seconds)
+    } else {
+        FixedOffset::east_opt(seconds)
+    }?;
+    Some((rest, Some(offset)))
+}
+
+impl FromStr for HackTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let error = || {
+            format!(
+                "`{s}` is not HH:MM:SS, DDD:HH:MM:SS or an ISO 8601 date and time, \
+                 optionally followed by Z or an offset"
+            )
+        };
+        // Dates contain a `-` at the fifth character, as in `2024-05-01`.
+        if s.get(4..5) == Some("-") {
+            if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+                return Ok(Self::At(time));
+            }
+            let (rest, offset) = split_offset(s).ok_or_else(error)?;
+            let time = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(rest, f).ok())
+                .ok_or_else(error)?;
+            return Ok(match offset {
+                Some(offset) => {
+                    Self::At(single(offset.from_local_datetime(&time)).ok_or_else(error)?)
+                }
+                None => Self::Local(time),
+            });
+        }
+        if s.matches(':').count() == 3
+            && s.bytes()
+                .all(|b| b.is_ascii_digit() || b == b':' || b == b'.')
+        {
+            let (day, time) = s.split_once(':').ok_or_else(error)?;
+            let day: u32 = day.parse().map_err(|_| error())?;
+            if !(1..=366).contains(&day) {
+                return Err(format!("day of year {day} is not between 1 and 366"));
+            }
+            return Ok(Self::DayOfYear(
+                day,
+                parse_time_of_day(time).ok_or_else(error)?,
+            ));
+        }
+        let (time, offset) = split_offset(s).ok_or_else(error)?;
+        Ok(Self::TimeOfDay(
+            parse_time_of_day(time).ok_or_else(error)?,
+            offset,
+        ))
+    }
+}
+
+impl TryFrom<String> for HackTime {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for HackTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const TIME: &str = "%H:%M:%S%.f";
+        match self {
+            Self::At(time) => f.write_str(&time.to_rfc3339()),
+            Self::Local(time) => write!(f, "{}", time.format("%Y-%m-%dT%H:%M:%S%.f")),
+            Self::TimeOfDay(time, None) => write!(f, "{}", time.format(TIME)),
+            Self::TimeOfDay(time, Some(offset)) => write!(f, "{}{offset}", time.format(TIME)),
+            Self::DayOfYear(day, time) => write!(f, "{day:03}:{}", time.format(TIME)),
+        }
+    }
+}
+
+impl From<HackTime> for String {
+    fn from(time: HackTime) -> Self {
+        time.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 1, h, m, s).unwrap()
+    }
+
+    #[test]
+    fn simulated_clock_is_shared_between_clones() {
+        let clock = SimulatedClock::new(at(9, 0, 0));
+        let handle = clock.clone();
+        handle.advance(Duration::seconds(90));
+        assert_eq!(clock.now(), at(9, 1, 30));
+        handle.set(at(10, 0, 0));
+        assert_eq!(clock.now(), at(10, 0, 0));
+        assert!(clock.health().is_good());
+    }
+
+    #[test]
+    fn hack_advances_with_the_source() {
+        let hack = TimeHack::new(at(13, 45, 0), at(9, 0, 0));
+        assert_eq!(hack.offset, Duration::minutes(285));
+        assert_eq!(hack.reference(), at(13, 45, 0));
+        assert_eq!(hack.apply(at(9, 2, 5)), at(13, 47, 5));
+    }
+
+    #[test]
+    fn hacks_serialize_as_reference_and_clock_times() {
+        let hack = TimeHack::new(at(13, 45, 0), at(9, 0, 0));
+        let json = serde_json::to_value(hack).unwrap();
+        let utc = |t: DateTime<Local>| serde_json::to_value(t.with_timezone(&Utc)).unwrap();
+        assert_eq!(json["reference"], utc(at(13, 45, 0)));
+        assert_eq!(json["clock"], utc(at(9, 0, 0)));
+        assert_eq!(serde_json::from_value::<TimeHack>(json).unwrap(), hack);
+    }
+
+    #[test]
+    fn drift_is_fitted_through_all_hacks() {
+        // Offsets of 0, 10 and 30 ms after 0, 1 and 2 hours: 20 ms per 2 h
+        // less the scatter of the middle hack.
+        let hacks: Vec<_> = [(0, 0), (1, 10), (2, 30)]
+            .into_iter()
+            .map(|(h, ms)| {
+                let clock = at(9 + h, 0, 0);
+                TimeHack::new(clock + Duration::milliseconds(ms), clock)
+            })
+            .collect();
+        let rate = clock_drift(&hacks).unwrap();
+        assert!((rate - 0.015 / 3600.0).abs() < 1e-12, "{rate}");
+        assert_eq!(clock_drift(&hacks[..1]), None);
+        assert_eq!(clock_drift(&[hacks[0], hacks[0]]), None);
+    }
+
+    #[test]
+    fn interpolates_only_between_hacks() {
+        // The clock loses 2 s over an hour against the reference.
+        let first = TimeHack::new(at(13, 0, 0), at(9, 0, 0));
+        let second = TimeHack::new(at(14, 0, 2), at(10, 0, 0));
+        let hacks = [first, second];
+        // Half way, stamped with the first offset, is 1 s late.
+        assert_eq!(
+            interpolate_hacks(&hacks, at(13, 30, 0)),
+            Some(at(13, 30, 1))
+        );
+        assert_eq!(interpolate_hacks(&hacks, at(13, 0, 0)), Some(at(13, 0, 0)));
+        assert_eq!(interpolate_hacks(&hacks, at(12, 59, 59)), None);
+        assert_eq!(interpolate_hacks(&hacks, at(14, 0, 0)), None);
+        assert_eq!(interpolate_hacks(&hacks[..1], at(13, 30, 0)), None);
+    }
+
+    fn utc(d: u32, h: u32, m: u32, s: u32) -> DateTime<Local> {
+        Utc.with_ymd_and_hms(2024, 5, d, h, m, s)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    fn resolve(input: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        input.parse::<HackTime>().unwrap().resolve(now)
+    }
+
+    #[test]
+    fn hack_times_resolve_to_nearest_day() {
+        let late = utc(1, 23, 59, 50);
+        assert_eq!(resolve("00:00:05Z", late), Some(utc(2, 0, 0, 5)));
+        assert_eq!(resolve("23:59:40+00:00", late), Some(utc(1, 23, 59, 40)));
+        assert_eq!(resolve("01:59:55+0200", late), Some(utc(1, 23, 59, 55)));
+        assert_eq!(resolve("19:00:00-05", late), Some(utc(2, 0, 0, 0)));
+        let early = utc(2, 0, 0, 10);
+        assert_eq!(
+            resolve("23:59:59.5Z", early).unwrap(),
+            utc(1, 23, 59, 59) + Duration::milliseconds(500)
+        );
+        // Local times land within half a day of now as well.
+        let local = resolve("12:00:00", early).unwrap();
+        assert!((local - early).abs() <= Duration::hours(12));
+        assert_eq!(local.time(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn hack_times_accept_dates_and_day_of_year() {
+        let now = utc(1, 12, 0, 0);
+        assert_eq!(resolve("2024-05-03T08:00:00Z", now), Some(utc(3, 8, 0, 0)));
+        assert_eq!(
+            resolve("2024-05-03 10:00:00+02:00", now),
+            Some(utc(3, 8, 0, 0))
+        );
+        assert_eq!(
+            resolve("2024-05-03T08:00:00", now),
+            Local.with_ymd_and_hms(2024, 5, 3, 8, 0, 0).single()
+        );
+        // 1 May 2024 is day 122 of a leap year.
+        assert_eq!(resolve("122:11:59:00", now), Some(utc(1, 11, 59, 0)));
+        let new_year = Utc
+            .with_ymd_and_hms(2025, 1, 1, 0, 0, 5)
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(
+            resolve("366:23:59:58", new_year),
+            Some(
+                Utc.with_ymd_and_hms(2024, 12, 31, 23, 59, 58)
+                    .unwrap()
+                    .with_timezone(&Local)
+            )
+        );
+    }
+
+    #[test]
+    fn hack_times_reject_malformed_input() {
+        for input in [
+            "",
+            "12:00",
+            "25:00:00",
+            "400:12:00:00",
+            "12:00:00+2",
+            "2024-13-01T00:00:00Z",
+        ] {
+            assert!(input.parse::<HackTime>().is_err(), "{input}");
+        }
+        for input in [
+            "12:00:00.250",
+            "12:00:00+02:00",
+            "045:01:02:03.5",
+            "2024-05-01T12:00:00+00:00",
+        ] {
+            let parsed: HackTime = input.parse().unwrap();
+            assert_eq!(
+                parsed.to_string().parse::<HackTime>(),
+                Ok(parsed),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn health_describes_staleness() {
+        assert_eq!(ClockHealth::Good.to_string(), "OK");
+        let stale = ClockHealth::Stale("no data for 5 s".into());
+        assert!(!stale.is_good());
+        assert_eq!(stale.to_string(), "stale: no data for 5 s");
+    }
+}
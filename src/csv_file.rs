@@ -0,0 +1,512 @@
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use std::io::{self, Write};
+use std::path::Path;
+use ulid::Ulid;
+
+use crate::{AppError, Entry, Note, Section, SessionMetadata, TimeHack};
+
+/// Value in the first column of the row identifying a versioned file.
+const FORMAT_MARKER: &str = "#rtnt";
+
+/// Newest file format version this build reads and the version it writes.
+///
+/// Version 0 is the original layout without a marker or header row.
+pub(crate) const CSV_VERSION: u32 = 1;
+
+/// Column names of the version 1 header row, in the order they are written.
+const COLUMNS: [&str; 10] = [
+    "type",
+    "timestamp",
+    "text",
+    "original_timestamp",
+    "pending",
+    "key",
+    "source",
+    "id",
+    "hack_reference",
+    "hack_clock",
+];
+
+/// Contents of a saved session.
+#[derive(Debug)]
+pub(crate) struct SessionFile {
+    pub(crate) metadata: SessionMetadata,
+    pub(crate) entries: Vec<Entry>,
+}
+
+/// Writes `metadata` and `entries` to `out` in the current format.
+///
+/// The file starts with a `#rtnt,<version>` marker row and a header row
+/// naming the columns, followed by the metadata and one row per entry.
+///
+/// # Errors
+/// Returns any I/O or serialization error.
+pub(crate) fn write(
+    out: impl Write,
+    metadata: &SessionMetadata,
+    time_source: &str,
+    entries: &[Entry],
+) -> io::Result<()> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_writer(out);
+    wtr.write_record([FORMAT_MARKER, &CSV_VERSION.to_string()])?;
+    wtr.write_record(COLUMNS)?;
+    let created = utc(metadata.created);
+    for (key, value) in [
+        ("title", metadata.title.as_str()),
+        ("operator", metadata.operator.as_str()),
+        ("created", created.as_str()),
+        ("time_source", time_source),
+    ] {
+        wtr.write_record(["meta", "", value, "", "", key, "", "", "", ""])?;
+    }
+    for (key, value) in &metadata.fields {
+        wtr.write_record(["field", "", value, "", "", key, "", "", "", ""])?;
+    }
+    for e in entries {
+        write_entry(&mut wtr, e)?;
+    }
+    wtr.flush()
+}
+
+/// Writes the row for a single entry to the end of an existing file.
+///
+/// The row uses the current column order, so it may only be appended to files
+/// for which [`is_current`] returns `true`.
+///
+/// # Errors
+/// Returns any I/O or serialization error.
+pub(crate) fn append(out: impl Write, entry: &Entry) -> io::Result<()> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_writer(out);
+    write_entry(&mut wtr, entry)?;
+    wtr.flush()
+}
+
+/// Returns `true` if the file at `path` starts with the marker and header
+/// rows [`write`] produces, so rows can be appended to it unchanged.
+///
+/// # Errors
+/// Returns the error from [`AppError::from_io`] if the file cannot be read,
+/// or [`AppError::Parse`] if its first rows are malformed.
+pub(crate) fn is_current(path: &Path) -> Result<bool, AppError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| csv_error(e, path))?;
+    let mut records = rdr.records();
+    let mut next = || records.next().transpose().map_err(|e| csv_error(e, path));
+    let version = CSV_VERSION.to_string();
+    let current = next()?.is_some_and(|r| r.iter().eq([FORMAT_MARKER, version.as_str()]))
+        && next()?.is_some_and(|r| r.iter().eq(COLUMNS));
+    Ok(current)
+}
+
+/// Formats a timestamp in UTC, the same way JSON files store it.
+fn utc(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn write_entry<W: Write>(wtr: &mut csv::Writer<W>, entry: &Entry) -> csv::Result<()> {
+    match entry {
+        Entry::Note(n) => {
+            let original = n.original_timestamp.map(utc).unwrap_or_default();
+            // A reset only has the clock source reading it returned to.
+            let (hack_reference, hack_clock) = match n.time_hack {
+                Some(h) => (utc(h.reference()), utc(h.set_at)),
+                None if n.hack_reset => (String::new(), utc(n.timestamp)),
+                None => Default::default(),
+            };
+            wtr.write_record([
+                "note",
+                &utc(n.timestamp),
+                &n.text,
+                &original,
+                if n.pending { "true" } else { "false" },
+                "",
+                n.source.as_deref().unwrap_or_default(),
+                &n.id.to_string(),
+                &hack_reference,
+                &hack_clock,
+            ])
+        }
+        Entry::Section(s) => wtr.write_record([
+            "section",
+            "",
+            &s.title,
+            "",
+            "",
+            "",
+            s.source.as_deref().unwrap_or_default(),
+            &s.id.to_string(),
+            "",
+            "",
+        ]),
+    }
+}
+
+/// Reads a session written in any supported format version.
+///
+/// Files without a marker row use the version 0 layout and are migrated on
+/// read. In versioned files columns are located by their header name, so
+/// columns added by later versions are ignored.
+///
+/// # Errors
+/// Returns [`AppError::UnsupportedVersion`] if the file was written by a newer
+/// version of the format, [`AppError::Parse`] with the line of the first
+/// malformed record, or the error from [`AppError::from_io`] if the file
+/// cannot be read.
+pub(crate) fn read(path: &Path) -> Result<SessionFile, AppError> {
+    let parse_error = |line: u64, message: String| AppError::Parse {
+        path: path.to_path_buf(),
+        line,
+        message,
+    };
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| csv_error(e, path))?;
+    let mut records = rdr.records();
+    let mut file = SessionFile {
+        metadata: SessionMetadata::default(),
+        entries: Vec::new(),
+    };
+    let Some(first) = records.next().transpose().map_err(|e| csv_error(e, path))? else {
+        return Ok(file);
+    };
+
+    let columns = if first.get(0) == Some(FORMAT_MARKER) {
+        let version = first
+            .get(1)
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .ok_or_else(|| {
+                parse_error(line_of(&first), "missing or invalid format version".into())
+            })?;
+        if version > CSV_VERSION {
+            return Err(AppError::UnsupportedVersion {
+                found: version,
+                supported: CSV_VERSION,
+            });
+        }
+        let header = records
+            .next()
+            .transpose()
+            .map_err(|e| csv_error(e, path))?
+            .ok_or_else(|| parse_error(line_of(&first) + 1, "missing header row".into()))?;
+        Columns::from_header(&header).map_err(|m| parse_error(line_of(&header), m))?
+    } else {
+        let columns = Columns::V0;
+        read_record(&first, &columns, &mut file).map_err(|m| parse_error(line_of(&first), m))?;
+        columns
+    };
+
+    for record in records {
+        let record = record.map_err(|e| csv_error(e, path))?;
+        read_record(&record, &columns, &mut file).map_err(|m| parse_error(line_of(&record), m))?;
+    }
+    Ok(file)
+}
+
+fn line_of(record: &csv::StringRecord) -> u64 {
+    record.position().map_or(0, csv::Position::line)
+}
+
+/// Converts a CSV reader error into an [`AppError`] for `path`.
+fn csv_error(error: csv::Error, path: &Path) -> AppError {
+    let line = error.position().map_or(0, csv::Position::line);
+    let message = error.to_string();
+    match error.into_kind() {
+        csv::ErrorKind::Io(e) => AppError::from_io(e, path),
+        _ => AppError::Parse {
+            path: path.to_path_buf(),
+            line,
+            message,
+        },
+    }
+}
+
+/// Positions of the known columns within a record.
+struct Columns {
+    kind: usize,
+    timestamp: Option<usize>,
+    text: Option<usize>,
+    original: Option<usize>,
+    pending: Option<usize>,
+    key: Option<usize>,
+    source: Option<usize>,
+    id: Option<usize>,
+    hack_reference: Option<usize>,
+    hack_clock: Option<usize>,
+    /// Whether the file predates explicit columns and uses the version 0 layout.
+    legacy: bool,
+}
+
+impl Columns {
+    /// Layout of headerless version 0 files.
+    ///
+    /// Metadata rows store their key in the timestamp column and notes without
+    /// text are pending.
+    const V0: Self = Self {
+        kind: 0,
+        timestamp: Some(1),
+        text: Some(2),
+        original: Some(3),
+        pending: None,
+        key: Some(1),
+        source: None,
+        id: None,
+        hack_reference: None,
+        hack_clock: None,
+        legacy: true,
+    };
+
+    fn from_header(header: &csv::StringRecord) -> Result<Self, String> {
+        let find = |name: &str| header.iter().position(|h| h.trim() == name);
+        Ok(Self {
+            kind: find("type").ok_or("header has no `type` column")?,
+            timestamp: find("timestamp"),
+            text: find("text"),
+            original: find("original_timestamp"),
+            pending: find("pending"),
+            key: find("key"),
+            source: find("source"),
+            id: find("id"),
+            hack_reference: find("hack_reference"),
+            hack_clock: find("hack_clock"),
+            legacy: false,
+        })
+    }
+}
+
+fn read_record(
+    record: &csv::StringRecord,
+    columns: &Columns,
+    file: &mut SessionFile,
+) -> Result<(), String> {
+    let get = |col: Option<usize>| col.and_then(|c| record.get(c)).unwrap_or_default();
+    let text = get(columns.text);
+    let source = Some(get(columns.source))
+        .filter(|s| !s.is_empty())
+        .map(str::to_string);
+    // Rows without an ID are given one derived from their contents after reading.
+    let id = match get(columns.id) {
+        "" => Ulid::nil(),
+        id => Ulid::from_string(id).map_err(|e| format!("invalid id `{id}`: {e}"))?,
+    };
+    match record.get(columns.kind).unwrap_or_default() {
+        "note" => {
+            let ts = parse_time(get(columns.timestamp))?;
+            let pending = if columns.legacy {
+                text.is_empty()
+            } else {
+                matches!(get(columns.pending), "true" | "1")
+            };
+            let mut note = Note::new(ts, text);
+            note.pending = pending;
+            note.source = source;
+            note.id = id;
+            let original = get(columns.original);
+            if !original.is_empty() {
+                note.original_timestamp = Some(parse_time(original)?);
+            }
+            let hack_reference = get(columns.hack_reference);
+            if !hack_reference.is_empty() {
+                note.time_hack = Some(TimeHack::new(
+                    parse_time(hack_reference)?,
+                    parse_time(get(columns.hack_clock))?,
+                ));
+            } else if !get(columns.hack_clock).is_empty() {
+                note.hack_reset = true;
+            }
+            file.entries.push(Entry::Note(note));
+        }
+        "section" => file.entries.push(Entry::Section(Section {
+            id,
+            title: text.to_string(),
+            source,
+        })),
+        "meta" => {
+            let meta = &mut file.metadata;
+            match get(columns.key) {
+                "title" => meta.title = text.to_string(),
+                "operator" => meta.operator = text.to_string(),
+                "created" => meta.created = parse_time(text)?,
+                "time_source" => meta.time_source = Some(text.to_string()),
+                other => log::warn!("Ignoring unknown metadata key `{other}`"),
+            }
+        }
+        "field" => file.metadata.set_field(get(columns.key), text),
+        // Rows of an unknown type were silently dropped by version 0 readers.
+        _ if columns.legacy => {}
+        other => return Err(format!("unknown record type `{other}`")),
+    }
+    Ok(())
+}
+
+fn parse_time(s: &str) -> Result<DateTime<Local>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Local))
+        .map_err(|e| format!("invalid timestamp `{s}`: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(h: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 1, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn writes_marker_and_header() {
+        let mut out = Vec::new();
+        write(&mut out, &SessionMetadata::default(), "System", &[]).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("#rtnt,1"));
+        assert_eq!(
+            lines.next(),
+            Some("type,timestamp,text,original_timestamp,pending,key,source,id,hack_reference,hack_clock")
+        );
+    }
+
+    #[test]
+    fn round_trips_described_note_without_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v1.csv");
+        let mut retimed = Note::new(time(11), "late");
+        retimed.retime(time(10));
+        retimed.source = Some("sim".into());
+        let mut hack = Note::new(time(8), "Time Hack");
+        hack.time_hack = Some(TimeHack::new(time(12), time(8)));
+        let mut section = Section::new("Climb");
+        section.source = Some("telemetry, bus 2".into());
+        let entries = vec![
+            Entry::Section(section),
+            Entry::Note(Note::new(time(9), "")),
+            Entry::Note(Note::pending(time(9))),
+            Entry::Note(retimed),
+            Entry::Section(Section::new("Climb")),
+            Entry::Note(hack),
+        ];
+        let file = std::fs::File::create(&path).unwrap();
+        write(file, &SessionMetadata::default(), "System", &entries).unwrap();
+        assert_eq!(read(&path).unwrap().entries, entries);
+    }
+
+    #[test]
+    fn stores_timestamps_in_utc() {
+        let at = DateTime::parse_from_rfc3339("2024-05-01T11:00:00.250+02:00")
+            .unwrap()
+            .with_timezone(&Local);
+        let mut out = Vec::new();
+        write(
+            &mut out,
+            &SessionMetadata::default(),
+            "System",
+            &[Entry::Note(Note::new(at, "a"))],
+        )
+        .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("note,2024-05-01T09:00:00.250Z,a,"), "{text}");
+    }
+
+    #[test]
+    fn migrates_v0_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v0.csv");
+        std::fs::write(
+            &path,
+            "meta,title,Sortie 3,\n\
+             section,,Intro,\n\
+             note,2024-05-01T09:00:00+00:00,,\n\
+             note,2024-05-01T10:00:00+00:00,hello,2024-05-01T09:30:00+00:00\n\
+             unknown,,ignored,\n",
+        )
+        .unwrap();
+        let file = read(&path).unwrap();
+        assert_eq!(file.metadata.title, "Sortie 3");
+        assert_eq!(file.entries.len(), 3);
+        assert!(matches!(&file.entries[1], Entry::Note(n) if n.pending));
+        assert!(matches!(&file.entries[2], Entry::Note(n) if n.original_timestamp.is_some()));
+    }
+
+    #[test]
+    fn ignores_unknown_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("extra.csv");
+        std::fs::write(
+            &path,
+            "#rtnt,1\n\
+             text,future,type,timestamp\n\
+             hello,x,note,2024-05-01T09:00:00+00:00\n",
+        )
+        .unwrap();
+        let file = read(&path).unwrap();
+        assert!(matches!(&file.entries[..], [Entry::Note(n)] if n.text == "hello"));
+    }
+
+    #[test]
+    fn rejects_invalid_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.csv");
+        std::fs::write(&path, "#rtnt,1\ntype,text,id\nsection,Intro,not-an-id\n").unwrap();
+        let err = read(&path).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("bad.csv:3: invalid id `not-an-id`"));
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v2.csv");
+        std::fs::write(&path, "#rtnt,2\ntype,timestamp,text\n").unwrap();
+        assert!(matches!(
+            read(&path),
+            Err(AppError::UnsupportedVersion {
+                found: 2,
+                supported: CSV_VERSION
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_record_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.csv");
+        std::fs::write(&path, "#rtnt,1\ntype,text\nmystery,hello\n").unwrap();
+        assert!(matches!(read(&path), Err(AppError::Parse { line: 3, .. })));
+    }
+
+    #[test]
+    fn reports_line_of_bad_timestamp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.csv");
+        std::fs::write(
+            &path,
+            "section,,Intro\nnote,2024-05-01T09:00:00+00:00,ok\nnote,09:00,broken\n",
+        )
+        .unwrap();
+        let err = read(&path).unwrap_err();
+        assert!(matches!(err, AppError::Parse { line: 3, .. }));
+        assert!(err
+            .to_string()
+            .contains("bad.csv:3: invalid timestamp `09:00`"));
+    }
+
+    #[test]
+    fn reports_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.csv");
+        assert!(matches!(read(&path), Err(AppError::NotFound(p)) if p == path));
+    }
+}
@@ -0,0 +1,567 @@
+use chrono::{DateTime, Local};
+use ratatui::style::Color;
+use std::fmt::Write as _;
+
+use crate::{DisplayZone, Entry, Note, Section, SessionMetadata, Theme};
+
+/// Number of columns in the note density strip of HTML reports.
+const TIMELINE_BINS: usize = 100;
+
+/// Document formats the note log can be exported to.
+///
+/// Exports are meant for reading and cannot be loaded back; use
+/// [`crate::App::save_to_file`] to keep an editable copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// Markdown with a heading per section and a bullet per note.
+    #[default]
+    Markdown,
+    /// Self-contained HTML page with a table per section and a timeline.
+    Html,
+}
+
+impl ExportFormat {
+    /// All available formats in menu order.
+    pub const ALL: [Self; 2] = [Self::Markdown, Self::Html];
+
+    /// Returns the file extension used for this format, without the dot.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+
+    /// Returns a human readable name of the format.
+    #[must_use]
+    pub const fn display_name(self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Html => "HTML",
+        }
+    }
+
+    /// Renders the session as a document in this format.
+    ///
+    /// # Arguments
+    /// * `metadata` - Session information shown at the top of the document.
+    /// * `time_source` - Name of the clock the notes were taken against.
+    /// * `entries` - Notes and sections in log order.
+    /// * `zone` - Time zone the timestamps are shown in.
+    /// * `theme` - Colors used by formats that support styling.
+    #[must_use]
+    pub fn render(
+        self,
+        metadata: &SessionMetadata,
+        time_source: &str,
+        entries: &[Entry],
+        zone: DisplayZone,
+        theme: &Theme,
+    ) -> String {
+        match self {
+            Self::Markdown => markdown(metadata, time_source, entries, zone),
+            Self::Html => html(metadata, time_source, entries, zone, theme),
+        }
+    }
+}
+
+fn markdown(
+    metadata: &SessionMetadata,
+    time_source: &str,
+    entries: &[Entry],
+    zone: DisplayZone,
+) -> String {
+    let mut out = String::new();
+    let title = if metadata.title.is_empty() {
+        "Session Notes"
+    } else {
+        &metadata.title
+    };
+    let _ = writeln!(out, "# {}\n", escape_markdown(title));
+
+    let mut details = vec![
+        (
+            "Created",
+            zone.format(metadata.created, "%Y-%m-%d %H:%M:%S %:z"),
+        ),
+        ("Time source", time_source.to_string()),
+    ];
+    if !metadata.operator.is_empty() {
+        details.insert(0, ("Operator", metadata.operator.clone()));
+    }
+    if zone != DisplayZone::Local {
+        details.push(("Time zone", zone.to_string()));
+    }
+    for (label, value) in details.iter().map(|(l, v)| (*l, v.as_str())).chain(
+        metadata
+            .fields
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str())),
+    ) {
+        let _ = writeln!(
+            out,
+            "- **{}:** {}",
+            escape_markdown(label),
+            escape_markdown(value)
+        );
+    }
+
+    let mut in_list = false;
+    for entry in entries {
+        match entry {
+            Entry::Section(s) => {
+                let _ = writeln!(out, "\n## {}", escape_markdown(&s.title));
+                in_list = false;
+            }
+            Entry::Note(n) => {
+                if !in_list {
+                    out.push('\n');
+                    in_list = true;
+                }
+                let _ = writeln!(out, "- {}", markdown_note(n, zone));
+            }
+        }
+    }
+    out
+}
+
+fn markdown_note(note: &Note, zone: DisplayZone) -> String {
+    let mut line = format!("`{}`", zone.format(note.timestamp, "%H:%M:%S%.3f"));
+    if note.pending {
+        line.push_str(" *(pending)*");
+    } else {
+        line.push(' ');
+        line.push_str(&escape_markdown(&note.text));
+    }
+    if let Some(original) = note.original_timestamp {
+        let _ = write!(
+            line,
+            " *(retimed from {})*",
+            zone.format(original, "%H:%M:%S%.3f")
+        );
+    }
+    if let Some(source) = &note.source {
+        let _ = write!(line, " *(from {})*", escape_markdown(source));
+    }
+    line
+}
+
+/// Escapes characters that would otherwise be read as Markdown formatting.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '#' | '|' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn html(
+    metadata: &SessionMetadata,
+    time_source: &str,
+    entries: &[Entry],
+    zone: DisplayZone,
+    theme: &Theme,
+) -> String {
+    let title = if metadata.title.is_empty() {
+        "Session Notes"
+    } else {
+        &metadata.title
+    };
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape_html(title),
+        html_style(theme),
+        escape_html(title)
+    );
+
+    out.push_str("<dl class=\"meta\">\n");
+    let created = zone.format(metadata.created, "%Y-%m-%d %H:%M:%S %:z");
+    let zone_name = match zone {
+        DisplayZone::Local => String::new(),
+        _ => zone.to_string(),
+    };
+    let details = [
+        ("Operator", metadata.operator.as_str()),
+        ("Created", created.as_str()),
+        ("Time source", time_source),
+        ("Time zone", zone_name.as_str()),
+    ];
+    for (label, value) in details.into_iter().filter(|(_, v)| !v.is_empty()).chain(
+        metadata
+            .fields
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str())),
+    ) {
+        let _ = writeln!(
+            out,
+            "<dt>{}</dt><dd>{}</dd>",
+            escape_html(label),
+            escape_html(value)
+        );
+    }
+    out.push_str("</dl>\n");
+
+    // Split the log into runs of notes, each headed by the section before it.
+    let mut groups: Vec<(Option<&Section>, Vec<&Note>)> = Vec::new();
+    for entry in entries {
+        match entry {
+            Entry::Section(s) => groups.push((Some(s), Vec::new())),
+            Entry::Note(n) => match groups.last_mut() {
+                Some((_, notes)) => notes.push(n),
+                None => groups.push((None, vec![n])),
+            },
+        }
+    }
+
+    let sections: Vec<(usize, &str)> = groups
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (section, _))| section.map(|s| (i, s.title.as_str())))
+        .collect();
+    if !sections.is_empty() {
+        out.push_str("<nav>\n<ul>\n");
+        for (i, title) in &sections {
+            let _ = writeln!(
+                out,
+                "<li><a href=\"#section-{i}\">{}</a></li>",
+                escape_html(title)
+            );
+        }
+        out.push_str("</ul>\n</nav>\n");
+    }
+
+    out.push_str(&timeline(&groups, zone));
+
+    for (i, (section, notes)) in groups.iter().enumerate() {
+        let _ = writeln!(out, "<section id=\"section-{i}\">");
+        if let Some(section) = section {
+            let _ = writeln!(
+                out,
+                "<h2 id=\"entry-{}\"><a href=\"#section-{i}\">{}</a></h2>",
+                section.id,
+                escape_html(&section.title)
+            );
+        }
+        if !notes.is_empty() {
+            out.push_str("<table>\n<thead><tr><th>Time</th><th>Note</th></tr></thead>\n<tbody>\n");
+            for note in notes {
+                out.push_str(&html_note(note, zone));
+            }
+            out.push_str("</tbody>\n</table>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn html_note(note: &Note, zone: DisplayZone) -> String {
+    let time = zone.format(note.timestamp, "%H:%M:%S%.3f");
+    let mut time_cell = format!("<td class=\"time\">{time}");
+    if let Some(original) = note.original_timestamp {
+        let _ = write!(
+            time_cell,
+            " <span class=\"retimed\" title=\"retimed from {0}\">(was {0})</span>",
+            zone.format(original, "%H:%M:%S%.3f")
+        );
+    }
+    time_cell.push_str("</td>");
+    let source = note.source.as_ref().map_or_else(String::new, |s| {
+        format!(" <span class=\"source\">from {}</span>", escape_html(s))
+    });
+    let text = if note.pending {
+        format!("<td class=\"pending\">pending{source}</td>")
+    } else {
+        format!("<td>{}{source}</td>", escape_html(&note.text))
+    };
+    format!("<tr id=\"entry-{}\">{time_cell}{text}</tr>\n", note.id)
+}
+
+/// Draws an SVG strip showing how many notes were taken over time.
+///
+/// Each section start is marked with a tick linking to its table.
+fn timeline(groups: &[(Option<&Section>, Vec<&Note>)], zone: DisplayZone) -> String {
+    let times: Vec<DateTime<Local>> = groups
+        .iter()
+        .flat_map(|(_, notes)| notes.iter().map(|n| n.timestamp))
+        .collect();
+    let (Some(first), Some(last)) = (times.iter().min(), times.iter().max()) else {
+        return String::new();
+    };
+    let span = (*last - *first).num_milliseconds().max(1);
+    let bin_of = |t: DateTime<Local>| {
+        let offset = (t - *first).num_milliseconds();
+        let last_bin = i64::try_from(TIMELINE_BINS - 1).unwrap_or(i64::MAX);
+        usize::try_from(offset * last_bin / span).unwrap_or(0)
+    };
+    let mut bins = [0_usize; TIMELINE_BINS];
+    for t in &times {
+        bins[bin_of(*t)] += 1;
+    }
+    let peak = bins.iter().copied().max().unwrap_or(1).max(1);
+
+    let mut out = format!(
+        "<figure class=\"timeline\">\n<svg viewBox=\"0 0 {TIMELINE_BINS} 20\" \
+         preserveAspectRatio=\"none\" role=\"img\" aria-label=\"Note density over time\">\n"
+    );
+    for (i, count) in bins.iter().enumerate().filter(|(_, c)| **c > 0) {
+        // Counts are small, so the conversion to f64 is exact.
+        #[allow(clippy::cast_precision_loss)]
+        let height = 18.0 * *count as f64 / peak as f64;
+        let _ = writeln!(
+            out,
+            "<rect class=\"bar\" x=\"{i}\" y=\"{:.2}\" width=\"1\" height=\"{height:.2}\">\
+             <title>{count} notes</title></rect>",
+            20.0 - height
+        );
+    }
+    for (i, (section, notes)) in groups.iter().enumerate() {
+        if let (Some(section), Some(note)) = (section, notes.first()) {
+            let x = bin_of(note.timestamp);
+            let _ = writeln!(
+                out,
+                "<a href=\"#section-{i}\"><rect class=\"tick\" x=\"{x}\" y=\"0\" \
+                 width=\"0.3\" height=\"20\"><title>{}</title></rect></a>",
+                escape_html(&section.title)
+            );
+        }
+    }
+    let _ = write!(
+        out,
+        "</svg>\n<figcaption>{} &ndash; {}</figcaption>\n</figure>\n",
+        zone.format(*first, "%H:%M:%S"),
+        zone.format(*last, "%H:%M:%S")
+    );
+    out
+}
+
+fn html_style(theme: &Theme) -> String {
+    format!(
+        "body {{ background: {bg}; color: {text}; font-family: sans-serif; margin: 2em; }}\n\
+         h1, h2, h2 a {{ color: {section}; text-decoration: none; }}\n\
+         a {{ color: {title}; }}\n\
+         dl.meta {{ display: grid; grid-template-columns: max-content auto; gap: 0.2em 1em; }}\n\
+         dt {{ font-weight: bold; color: {time}; }}\n\
+         dd {{ margin: 0; }}\n\
+         table {{ border-collapse: collapse; width: 100%; margin-bottom: 1.5em; }}\n\
+         th {{ background: {head_bg}; color: {head_fg}; text-align: left; }}\n\
+         th, td {{ border: 1px solid {border}; padding: 0.3em 0.6em; }}\n\
+         td.time {{ color: {time}; font-family: monospace; font-weight: bold; white-space: nowrap; width: 1%; }}\n\
+         td.pending {{ font-style: italic; opacity: 0.6; }}\n\
+         .retimed, .source {{ font-weight: normal; font-style: normal; opacity: 0.7; }}\n\
+         figure.timeline {{ margin: 1em 0 2em; }}\n\
+         figure.timeline svg {{ width: 100%; height: 4em; border: 1px solid {border}; }}\n\
+         figure.timeline .bar {{ fill: {time}; }}\n\
+         figure.timeline .tick {{ fill: {section}; }}\n\
+         figcaption {{ font-size: 0.8em; }}\n",
+        bg = css_color(theme.overlay_bg),
+        text = css_color(theme.note_fg),
+        section = css_color(theme.section_fg),
+        title = css_color(theme.notes_title),
+        time = css_color(theme.timestamp_fg),
+        head_bg = css_color(theme.notes_highlight_bg),
+        head_fg = css_color(theme.notes_highlight_fg),
+        border = css_color(theme.notes_border),
+    )
+}
+
+/// Converts a terminal color to the CSS color closest to its usual rendering.
+fn css_color(color: Color) -> String {
+    const ANSI: [&str; 16] = [
+        "#000000", "#800000", "#008000", "#808000", "#000080", "#800080", "#008080", "#c0c0c0",
+        "#808080", "#ff0000", "#00ff00", "#ffff00", "#0000ff", "#ff00ff", "#00ffff", "#ffffff",
+    ];
+    let index = match color {
+        Color::Reset => return "inherit".to_string(),
+        Color::Rgb(r, g, b) => return format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::Indexed(i) => i,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+    };
+    match index {
+        0..=15 => ANSI[usize::from(index)].to_string(),
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = index - 16;
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(i / 36),
+                level(i / 6 % 6),
+                level(i % 6)
+            )
+        }
+        _ => {
+            let v = 8 + (index - 232) * 10;
+            format!("#{v:02x}{v:02x}{v:02x}")
+        }
+    }
+}
+
+/// Escapes text for use in HTML element content and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ThemeName;
+    use chrono::TimeZone;
+
+    fn theme() -> Theme {
+        ThemeName::Matrix.theme()
+    }
+
+    #[test]
+    fn markdown_renders_sections_and_notes() {
+        let at = |s| Local.with_ymd_and_hms(2024, 5, 1, 9, 0, s).unwrap();
+        let mut metadata = SessionMetadata {
+            title: "Sortie 3".into(),
+            operator: "J. Doe".into(),
+            created: at(0),
+            ..SessionMetadata::default()
+        };
+        metadata.set_field("aircraft", "N123AB");
+        let mut retimed = Note::new(at(5), "Gear up");
+        retimed.retime(at(4));
+        let entries = vec![
+            Entry::Note(Note::new(at(1), "Time Hack: 09:00:01 -> 12:00:00")),
+            Entry::Section(Section::new("Takeoff")),
+            Entry::Note(Note::new(at(2), "Brakes *released*")),
+            Entry::Note(Note::pending(at(3))),
+            Entry::Note(retimed),
+        ];
+        let md = ExportFormat::Markdown.render(
+            &metadata,
+            "Time Hack",
+            &entries,
+            DisplayZone::Local,
+            &theme(),
+        );
+        let expected = format!(
+            "# Sortie 3\n\n\
+             - **Operator:** J. Doe\n\
+             - **Created:** {}\n\
+             - **Time source:** Time Hack\n\
+             - **aircraft:** N123AB\n\n\
+             - `09:00:01.000` Time Hack: 09:00:01 -> 12:00:00\n\n\
+             ## Takeoff\n\n\
+             - `09:00:02.000` Brakes \\*released\\*\n\
+             - `09:00:03.000` *(pending)*\n\
+             - `09:00:04.000` Gear up *(retimed from 09:00:05.000)*\n",
+            at(0).format("%Y-%m-%d %H:%M:%S %:z")
+        );
+        assert_eq!(md, expected);
+    }
+
+    #[test]
+    fn markdown_without_title_uses_default_heading() {
+        let md = ExportFormat::Markdown.render(
+            &SessionMetadata::default(),
+            "System",
+            &[],
+            DisplayZone::Local,
+            &theme(),
+        );
+        assert!(md.starts_with("# Session Notes\n"));
+        assert!(!md.contains("Operator"));
+    }
+
+    #[test]
+    fn html_is_self_contained_and_themed() {
+        let at = |m| Local.with_ymd_and_hms(2024, 5, 1, 9, m, 0).unwrap();
+        let entries = vec![
+            Entry::Note(Note::new(at(0), "before <any> section")),
+            Entry::Section(Section::new("Climb & cruise")),
+            Entry::Note(Note::new(at(10), "level off")),
+            Entry::Note(Note::pending(at(20))),
+        ];
+        let metadata = SessionMetadata {
+            title: "Sortie \"3\"".into(),
+            ..SessionMetadata::default()
+        };
+        let html =
+            ExportFormat::Html.render(&metadata, "System", &entries, DisplayZone::Local, &theme());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Sortie &quot;3&quot;</title>"));
+        assert!(html.contains("<a href=\"#section-1\">Climb &amp; cruise</a>"));
+        assert!(html.contains("<section id=\"section-1\">"));
+        assert!(html.contains(&format!("<tr id=\"entry-{}\">", entries[2].id())));
+        assert!(html.contains(&format!("<h2 id=\"entry-{}\">", entries[1].id())));
+        assert!(html.contains("before &lt;any&gt; section"));
+        assert!(html.contains("<td class=\"pending\">pending</td>"));
+        assert!(html.contains("<svg"));
+        assert_eq!(html.matches("class=\"bar\"").count(), 3);
+        // Matrix theme: light green timestamps on black.
+        assert!(html.contains("background: #000000"));
+        assert!(html.contains("fill: #00ff00"));
+        assert!(!html.contains("src="));
+        assert!(!html.contains("<link"));
+    }
+
+    #[test]
+    fn exports_show_times_in_the_chosen_zone() {
+        let at = chrono::Utc
+            .with_ymd_and_hms(2024, 5, 1, 22, 30, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let metadata = SessionMetadata {
+            created: at,
+            ..SessionMetadata::default()
+        };
+        let entries = vec![Entry::Note(Note::new(at, "engine start"))];
+        let tokyo: DisplayZone = "Asia/Tokyo".parse().unwrap();
+        let md = ExportFormat::Markdown.render(&metadata, "System", &entries, tokyo, &theme());
+        assert!(md.contains("- **Created:** 2024-05-02 07:30:00 +09:00\n"));
+        assert!(md.contains("- **Time zone:** Asia/Tokyo\n"));
+        assert!(md.contains("- `07:30:00.000` engine start\n"));
+        let html = ExportFormat::Html.render(&metadata, "System", &entries, tokyo, &theme());
+        assert!(html.contains("<dt>Time zone</dt><dd>Asia/Tokyo</dd>"));
+        assert!(html.contains("<td class=\"time\">07:30:00.000</td>"));
+    }
+
+    #[test]
+    fn css_colors_cover_palettes() {
+        assert_eq!(css_color(Color::Rgb(1, 2, 255)), "#0102ff");
+        assert_eq!(css_color(Color::Indexed(9)), "#ff0000");
+        assert_eq!(css_color(Color::Indexed(196)), "#ff0000");
+        assert_eq!(css_color(Color::Indexed(232)), "#080808");
+        assert_eq!(css_color(Color::Indexed(255)), "#eeeeee");
+    }
+}
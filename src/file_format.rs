@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+use ulid::Ulid;
+
+use crate::csv_file::{self, SessionFile};
+use crate::{json_file, AppError, Entry, SessionMetadata};
+
+/// Formats a session can be saved in and loaded back from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileFormat {
+    /// Comma separated values with a marker and header row.
+    #[default]
+    Csv,
+    /// A single JSON document holding the metadata and all entries.
+    Json,
+    /// JSON Lines: a header line followed by one entry per line.
+    JsonLines,
+}
+
+impl FileFormat {
+    /// Every supported format, in the order they are offered.
+    pub const ALL: [Self; 3] = [Self::Csv, Self::Json, Self::JsonLines];
+
+    /// File extension used for the format, without the leading dot.
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::JsonLines => "jsonl",
+        }
+    }
+
+    /// Returns the format with the given extension, ignoring case.
+    #[must_use]
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|f| f.extension().eq_ignore_ascii_case(ext))
+    }
+
+    /// Chooses the format for `path` from its extension.
+    ///
+    /// Paths with any other extension are treated as CSV, the format every
+    /// earlier release wrote regardless of the file name.
+    ///
+    /// # Example
+    /// ```
+    /// use real_time_note_taker::FileFormat;
+    ///
+    /// assert_eq!(FileFormat::from_path("log.JSONL"), FileFormat::JsonLines);
+    /// assert_eq!(FileFormat::from_path("notes.txt"), FileFormat::Csv);
+    /// ```
+    #[must_use]
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        path.as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::from_extension)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn write(
+        self,
+        out: impl Write,
+        metadata: &SessionMetadata,
+        time_source: &str,
+        entries: &[Entry],
+    ) -> io::Result<()> {
+        match self {
+            Self::Csv => csv_file::write(out, metadata, time_source, entries),
+            Self::Json => json_file::write_json(out, metadata, time_source, entries),
+            Self::JsonLines => json_file::write_jsonl(out, metadata, time_source, entries),
+        }
+    }
+
+    /// Returns `true` if entries can be written to the end of the existing file
+    /// at `path` with [`append`](Self::append).
+    ///
+    /// JSON documents and CSV files in an older layout must be rewritten
+    /// instead.
+    pub(crate) fn can_append(self, path: &Path) -> Result<bool, AppError> {
+        match self {
+            Self::Csv => csv_file::is_current(path),
+            Self::JsonLines => Ok(true),
+            Self::Json => Ok(false),
+        }
+    }
+
+    /// Writes a single entry to the end of an existing file in this format.
+    ///
+    /// JSON documents cannot be extended this way and must be rewritten.
+    pub(crate) fn append(self, out: impl Write, entry: &Entry) -> io::Result<()> {
+        match self {
+            Self::Csv => csv_file::append(out, entry),
+            Self::JsonLines => json_file::append_jsonl(out, entry),
+            Self::Json => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "JSON documents cannot be appended to",
+            )),
+        }
+    }
+
+    /// Reads a session, giving entries that were saved without an ID one
+    /// derived from their position and contents.
+    ///
+    /// Derived IDs are the same every time the file is read, so an older file
+    /// can be merged and streamed consistently before it is first re-saved.
+    /// Duplicated IDs, as left by copying rows by hand, are replaced the same
+    /// way.
+    pub(crate) fn read(self, path: &Path) -> Result<SessionFile, AppError> {
+        let mut file = match self {
+            Self::Csv => csv_file::read(path),
+            Self::Json => json_file::read_json(path),
+            Self::JsonLines => json_file::read_jsonl(path),
+        }?;
+        let mut seen = HashSet::new();
+        for (index, entry) in file.entries.iter_mut().enumerate() {
+            let id = entry.id();
+            if id.is_nil() || !seen.insert(id) {
+                let id = derived_id(index, entry);
+                seen.insert(id);
+                match entry {
+                    Entry::Note(n) => n.id = id,
+                    Entry::Section(s) => s.id = id,
+                }
+            }
+        }
+        Ok(file)
+    }
+}
+
+/// Builds a stable ID for an entry read without one.
+///
+/// The time part is the note's timestamp, or zero for sections, and the
+/// random part is an FNV-1a hash of the position and contents.
+fn derived_id(index: usize, entry: &Entry) -> Ulid {
+    let (ms, kind, text) = match entry {
+        Entry::Note(n) => (n.timestamp.timestamp_millis(), "note", n.text.as_str()),
+        Entry::Section(s) => (0, "section", s.title.as_str()),
+    };
+    let mut hash: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+    for byte in (index as u64)
+        .to_le_bytes()
+        .iter()
+        .chain(kind.as_bytes())
+        .chain(text.as_bytes())
+    {
+        hash ^= u128::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0000_0100_0000_0000_0000_0000_013b);
+    }
+    Ulid::from_parts(u64::try_from(ms).unwrap_or_default(), hash)
+}
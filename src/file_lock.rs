@@ -0,0 +1,106 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// A log file held under an exclusive advisory lock.
+///
+/// Every writer in `rtnt` goes through this type, so an interactive session
+/// and `rtnt add` never write the same file at the same time. The lock is
+/// released when the value is dropped.
+pub(crate) struct LockedFile {
+    file: File,
+}
+
+impl LockedFile {
+    /// Opens `path` for writing, creating it if needed, and waits for the lock.
+    ///
+    /// The contents are left untouched so they can still be read.
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        file.lock()?;
+        Ok(Self { file })
+    }
+
+    /// Returns `true` if the file has no contents yet.
+    pub(crate) fn is_empty(&self) -> io::Result<bool> {
+        Ok(self.file.metadata()?.len() == 0)
+    }
+
+    /// Replaces the contents of the file with what `write` produces.
+    ///
+    /// The file is only truncated once `write` has succeeded, so an error
+    /// while serializing leaves the old contents in place.
+    pub(crate) fn replace(
+        &mut self,
+        write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut contents = Vec::new();
+        write(&mut contents)?;
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&contents)
+    }
+
+    /// Adds what `write` produces to the end of the file.
+    ///
+    /// A line break is inserted first if the last line is unterminated, so the
+    /// new record never runs into the previous one.
+    pub(crate) fn append(
+        &mut self,
+        write: impl FnOnce(&mut BufWriter<&File>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let len = self.file.seek(SeekFrom::End(0))?;
+        let mut out = BufWriter::new(&self.file);
+        if len > 0 {
+            let mut last = [0];
+            out.get_mut().seek(SeekFrom::End(-1))?;
+            out.get_mut().read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                out.write_all(b"\n")?;
+            }
+        }
+        write(&mut out)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_terminates_last_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.csv");
+        std::fs::write(&path, "a").unwrap();
+        let mut file = LockedFile::open(&path).unwrap();
+        assert!(!file.is_empty().unwrap());
+        file.append(|out| out.write_all(b"b\n")).unwrap();
+        file.append(|out| out.write_all(b"c\n")).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\nc\n");
+        file.replace(|out| out.write_all(b"d\n")).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "d\n");
+        let failed = file.replace(|out| {
+            out.write_all(b"partial")?;
+            Err(io::Error::other("serialization failed"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "d\n");
+    }
+
+    #[test]
+    fn lock_is_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.csv");
+        let held = LockedFile::open(&path).unwrap();
+        let other = File::open(&path).unwrap();
+        assert!(other.try_lock().is_err());
+        drop(held);
+        assert!(other.try_lock().is_ok());
+    }
+}
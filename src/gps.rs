@@ -0,0 +1,314 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Utc};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::clock::{ClockHealth, ClockSource};
+
+/// How long the last fix is trusted without a new sentence before falling
+/// back to the system clock. Receivers send a fix every second.
+const STALE_AFTER: Duration = Duration::from_secs(5);
+
+/// How long to wait before reopening a device or FIFO that stopped sending.
+const REOPEN_DELAY: Duration = Duration::from_secs(1);
+
+/// How long to wait before receiving again after a socket error.
+const RECV_RETRY: Duration = Duration::from_secs(1);
+
+/// Largest datagram accepted; NMEA sentences are at most 82 characters.
+const MAX_DATAGRAM: usize = 1024;
+
+/// A time reported by the receiver and when it arrived.
+#[derive(Debug, Clone, Copy)]
+struct Fix {
+    time: DateTime<Utc>,
+    received: Instant,
+}
+
+/// A clock disciplined to the UTC time in NMEA `RMC` and `ZDA` sentences from
+/// a GPS receiver.
+///
+/// Sentences are read by a background thread. Between sentences the clock
+/// advances from the last fix on the monotonic system timer. When no valid
+/// sentence has arrived for a few seconds the system clock is used instead
+/// and [`health`](ClockSource::health) reports the feed as stale.
+#[derive(Debug, Clone)]
+pub struct GpsClock {
+    last_fix: Arc<Mutex<Option<Fix>>>,
+    local_addr: Option<SocketAddr>,
+}
+
+impl GpsClock {
+    /// Reads sentences from a serial device, FIFO or file at `path`.
+    ///
+    /// Serial devices must already be set to the receiver's baud rate, for
+    /// example with `stty`. A regular file is read once, which replays a
+    /// recorded feed and then goes stale; devices and FIFOs are reopened
+    /// whenever they stop sending.
+    ///
+    /// # Errors
+    /// Returns an error if `path` does not exist or cannot be inspected.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let once = std::fs::metadata(&path)?.is_file();
+        let clock = Self::new(None);
+        let last_fix = Arc::clone(&clock.last_fix);
+        // Devices and FIFOs are opened by the reader since opening them can
+        // block until the other end is ready.
+        std::thread::spawn(move || read_path(&path, once, &last_fix));
+        Ok(clock)
+    }
+
+    /// Receives sentences as UDP datagrams on `addr`.
+    ///
+    /// Each datagram may hold one or more sentences separated by line breaks.
+    ///
+    /// # Errors
+    /// Returns an error if the socket cannot be bound.
+    pub fn listen_udp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        let clock = Self::new(Some(socket.local_addr()?));
+        let last_fix = Arc::clone(&clock.last_fix);
+        std::thread::spawn(move || {
+            let mut buf = [0; MAX_DATAGRAM];
+            loop {
+                let len = match socket.recv(&mut buf) {
+                    Ok(len) => len,
+                    Err(e) => {
+                        log::warn!("Failed to receive GPS datagram: {e}");
+                        std::thread::sleep(RECV_RETRY);
+                        continue;
+                    }
+                };
+                let received = Instant::now();
+                for line in String::from_utf8_lossy(&buf[..len]).lines() {
+                    record(line, received, &last_fix);
+                }
+            }
+        });
+        Ok(clock)
+    }
+
+    fn new(local_addr: Option<SocketAddr>) -> Self {
+        Self {
+            last_fix: Arc::new(Mutex::new(None)),
+            local_addr,
+        }
+    }
+
+    /// Returns the address bound by [`listen_udp`](Self::listen_udp), which
+    /// tells the port chosen for port 0.
+    #[must_use]
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Returns the last fix if it is recent enough to be used.
+    fn fresh_fix(&self) -> Result<Fix, ClockHealth> {
+        let last = *self.last_fix.lock().unwrap_or_else(PoisonError::into_inner);
+        match last {
+            Some(fix) if fix.received.elapsed() < STALE_AFTER => Ok(fix),
+            Some(fix) => Err(ClockHealth::Stale(format!(
+                "no GPS fix for {} s",
+                fix.received.elapsed().as_secs()
+            ))),
+            None => Err(ClockHealth::Stale("waiting for GPS fix".into())),
+        }
+    }
+}
+
+impl ClockSource for GpsClock {
+    fn name(&self) -> &'static str {
+        "GPS"
+    }
+
+    fn now(&self) -> DateTime<Local> {
+        match self.fresh_fix() {
+            Ok(fix) => {
+                let elapsed =
+                    chrono::Duration::from_std(fix.received.elapsed()).unwrap_or_default();
+                (fix.time + elapsed).with_timezone(&Local)
+            }
+            Err(_) => Local::now(),
+        }
+    }
+
+    fn health(&self) -> ClockHealth {
+        self.fresh_fix().err().unwrap_or(ClockHealth::Good)
+    }
+}
+
+/// Reads sentences from `path`, reopening it after it ends unless `once`.
+fn read_path(path: &Path, once: bool, last_fix: &Mutex<Option<Fix>>) {
+    loop {
+        match File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let Ok(line) = line else { break };
+                    record(&line, Instant::now(), last_fix);
+                }
+            }
+            Err(e) => log::warn!("Cannot open GPS feed {}: {e}", path.display()),
+        }
+        if once {
+            return;
+        }
+        std::thread::sleep(REOPEN_DELAY);
+    }
+}
+
+/// Stores the time in `line` as the latest fix if it is a valid sentence.
+fn record(line: &str, received: Instant, last_fix: &Mutex<Option<Fix>>) {
+    match parse_sentence(line.trim()) {
+        Ok(Some(time)) => {
+            *last_fix.lock().unwrap_or_else(PoisonError::into_inner) = Some(Fix { time, received });
+        }
+        Ok(None) => {}
+        Err(e) => log::debug!("Ignoring NMEA sentence `{}`: {e}", line.trim()),
+    }
+}
+
+/// Extracts the UTC time from an `RMC` or `ZDA` sentence from any talker.
+///
+/// # Returns
+/// `Ok(None)` for other sentence types and for `RMC` sentences without a
+/// valid fix.
+fn parse_sentence(line: &str) -> Result<Option<DateTime<Utc>>, String> {
+    let Some(body) = line.strip_prefix('$') else {
+        return Ok(None);
+    };
+    let body = match body.split_once('*') {
+        Some((body, checksum)) => {
+            let expected = u8::from_str_radix(checksum.trim(), 16)
+                .map_err(|_| format!("invalid checksum `{checksum}`"))?;
+            let actual = body.bytes().fold(0, |sum, b| sum ^ b);
+            if actual != expected {
+                return Err(format!(
+                    "checksum {actual:02X} does not match {expected:02X}"
+                ));
+            }
+            body
+        }
+        None => body,
+    };
+    let fields: Vec<&str> = body.split(',').collect();
+    let kind = fields[0].get(2..).unwrap_or_default();
+    let field = |i: usize| fields.get(i).copied().unwrap_or_default();
+    let (time, date) = match kind {
+        "RMC" => {
+            if field(2) != "A" {
+                return Ok(None);
+            }
+            let date = NaiveDate::parse_from_str(field(9), "%d%m%y")
+                .map_err(|e| format!("invalid date `{}`: {e}", field(9)))?;
+            (field(1), date)
+        }
+        "ZDA" => {
+            let number = |i: usize| {
+                field(i)
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid date field `{}`", field(i)))
+            };
+            let year = i32::try_from(number(4)?).map_err(|e| e.to_string())?;
+            let date = NaiveDate::from_ymd_opt(year, number(3)?, number(2)?)
+                .ok_or_else(|| "invalid date".to_string())?;
+            (field(1), date)
+        }
+        _ => return Ok(None),
+    };
+    let time = NaiveTime::parse_from_str(time, "%H%M%S%.f")
+        .map_err(|e| format!("invalid time `{time}`: {e}"))?;
+    Ok(Some(date.and_time(time).and_utc()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const RMC: &str = "$GPRMC,123519.50,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*41";
+    const ZDA: &str = "$GPZDA,201530.00,04,07,2002,00,00*60";
+
+    #[test]
+    fn parses_rmc_and_zda_times() {
+        assert_eq!(
+            parse_sentence(RMC).unwrap(),
+            Some(
+                Utc.with_ymd_and_hms(1994, 3, 23, 12, 35, 19).unwrap()
+                    + chrono::Duration::milliseconds(500)
+            )
+        );
+        assert_eq!(
+            parse_sentence(ZDA).unwrap(),
+            Some(Utc.with_ymd_and_hms(2002, 7, 4, 20, 15, 30).unwrap())
+        );
+        assert!(parse_sentence("$GNZDA,201530.00,04,07,2002,00,00")
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn ignores_other_and_invalid_sentences() {
+        assert_eq!(parse_sentence("$GPGGA,123519,4807.038,N").unwrap(), None);
+        assert_eq!(
+            parse_sentence("$GPRMC,123519,V,,,,,,,230394,,").unwrap(),
+            None
+        );
+        assert_eq!(parse_sentence("garbage").unwrap(), None);
+        assert!(parse_sentence(&RMC.replace("*41", "*42")).is_err());
+        assert!(parse_sentence("$GPZDA,201530.00,31,02,2002,00,00").is_err());
+    }
+
+    #[test]
+    fn replays_a_recorded_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("feed.nmea");
+        std::fs::write(&path, format!("$GPGSV,1,1,00\n{RMC}\n{ZDA}\n")).unwrap();
+        let clock = GpsClock::open(&path).unwrap();
+        let start = Instant::now();
+        while !clock.health().is_good() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(clock.health(), ClockHealth::Good);
+        let expected = Utc.with_ymd_and_hms(2002, 7, 4, 20, 15, 30).unwrap();
+        let offset = clock.now().with_timezone(&Utc) - expected;
+        assert!(offset >= chrono::Duration::zero() && offset < chrono::Duration::seconds(1));
+        assert!(GpsClock::open(dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_system_time_when_stale() {
+        let clock = GpsClock::new(None);
+        assert_eq!(
+            clock.health(),
+            ClockHealth::Stale("waiting for GPS fix".into())
+        );
+        let received = Instant::now().checked_sub(STALE_AFTER).unwrap();
+        record(ZDA, received, &clock.last_fix);
+        assert!(
+            matches!(clock.health(), ClockHealth::Stale(m) if m.starts_with("no GPS fix for 5"))
+        );
+        let drift = Local::now() - clock.now();
+        assert!(drift.num_seconds().abs() < 1);
+    }
+
+    #[test]
+    fn receives_sentences_over_udp() {
+        let clock = GpsClock::listen_udp("127.0.0.1:0").unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client
+            .send_to(RMC.as_bytes(), clock.local_addr().unwrap())
+            .unwrap();
+        let start = Instant::now();
+        while !clock.health().is_good() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            clock.now().with_timezone(&Utc).date_naive().to_string(),
+            "1994-03-23"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use ulid::Ulid;
+
+use crate::journal::Change;
+use crate::{SessionMetadata, TimeHack};
+
+/// Maximum number of undo steps kept in memory.
+const HISTORY_LIMIT: usize = 500;
+
+/// Time hack state as stored by [`crate::App`].
+pub(crate) type TimeHackState = Option<TimeHack>;
+
+/// The file a log belongs to, as stored by [`crate::App`].
+///
+/// Operations that switch to another file, such as loading one, record it
+/// so undoing them also switches back.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionState {
+    pub(crate) file_path: Option<PathBuf>,
+    pub(crate) saved_ids: HashSet<Ulid>,
+    pub(crate) metadata: SessionMetadata,
+    pub(crate) dirty: bool,
+}
+
+/// Changes that revert one user operation.
+#[derive(Debug, Clone)]
+pub(crate) struct Step {
+    /// Changes to apply, in order, to revert the operation.
+    pub(crate) changes: Vec<Change>,
+    /// Time hack to restore when the operation modified the clock.
+    pub(crate) time_hack: Option<TimeHackState>,
+    /// File to return to when the operation switched to another one.
+    pub(crate) session: Option<SessionState>,
+}
+
+/// Undo and redo stacks for entry mutations.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+}
+
+impl History {
+    /// Records the step that reverts a new operation and clears the redo stack.
+    pub(crate) fn record(&mut self, step: Step) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(step);
+        self.redo.clear();
+    }
+
+    /// Takes the most recent undo step.
+    pub(crate) fn pop_undo(&mut self) -> Option<Step> {
+        self.undo.pop()
+    }
+
+    /// Takes the most recent redo step.
+    pub(crate) fn pop_redo(&mut self) -> Option<Step> {
+        self.redo.pop()
+    }
+
+    /// Stores a step that re-applies an undone operation.
+    pub(crate) fn push_redo(&mut self, step: Step) {
+        self.redo.push(step);
+    }
+
+    /// Stores a step that reverts a redone operation without clearing redo.
+    pub(crate) fn push_undo(&mut self, step: Step) {
+        self.undo.push(step);
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
@@ -0,0 +1,379 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use ulid::Ulid;
+
+use crate::Entry;
+
+/// Extension of write-ahead journals inside the save directory.
+const JOURNAL_EXTENSION: &str = "journal";
+
+/// Name of the single journal shared by every session in older releases.
+const LEGACY_JOURNAL: &str = "session.journal";
+
+/// A single mutation of the entry list.
+///
+/// Every change to [`crate::App::entries`] is expressed as a `Change` so it can
+/// be appended to the journal and replayed after a crash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Change {
+    /// Insert an entry at the given index.
+    Insert { index: usize, entry: Entry },
+    /// Replace the entry at the given index.
+    Update { index: usize, entry: Entry },
+    /// Remove the entry at the given index.
+    Remove { index: usize },
+    /// Move the entry at `from` so that it ends up at index `to`.
+    Move { from: usize, to: usize },
+    /// Replace all entries.
+    Reset { entries: Vec<Entry> },
+}
+
+impl Change {
+    /// Returns the change that reverts `self` when applied after it.
+    ///
+    /// # Arguments
+    /// * `entries` - The entries as they are before `self` is applied.
+    pub(crate) fn inverse(&self, entries: &[Entry]) -> Self {
+        match self {
+            Self::Insert { index, .. } => Self::Remove {
+                index: (*index).min(entries.len()),
+            },
+            Self::Update { index, entry } => match entries.get(*index) {
+                Some(old) => Self::Update {
+                    index: *index,
+                    entry: old.clone(),
+                },
+                None => Self::Update {
+                    index: *index,
+                    entry: entry.clone(),
+                },
+            },
+            Self::Remove { index } => match entries.get(*index) {
+                Some(old) => Self::Insert {
+                    index: *index,
+                    entry: old.clone(),
+                },
+                None => Self::Reset {
+                    entries: entries.to_vec(),
+                },
+            },
+            Self::Move { from, to } => Self::Move {
+                from: *to,
+                to: *from,
+            },
+            Self::Reset { .. } => Self::Reset {
+                entries: entries.to_vec(),
+            },
+        }
+    }
+
+    /// Returns the index of the entry most affected by the change, if any.
+    pub(crate) fn focus(&self) -> Option<usize> {
+        match self {
+            Self::Insert { index, .. } | Self::Update { index, .. } | Self::Remove { index } => {
+                Some(*index)
+            }
+            Self::Move { to, .. } => Some(*to),
+            Self::Reset { .. } => None,
+        }
+    }
+
+    /// Applies the change to `entries`, ignoring out of range indices.
+    pub(crate) fn apply(self, entries: &mut Vec<Entry>) {
+        match self {
+            Self::Insert { index, entry } => {
+                let index = index.min(entries.len());
+                entries.insert(index, entry);
+            }
+            Self::Update { index, entry } => {
+                if let Some(slot) = entries.get_mut(index) {
+                    *slot = entry;
+                }
+            }
+            Self::Remove { index } => {
+                if index < entries.len() {
+                    entries.remove(index);
+                }
+            }
+            Self::Move { from, to } => {
+                if from < entries.len() && to < entries.len() {
+                    let entry = entries.remove(from);
+                    entries.insert(to, entry);
+                }
+            }
+            Self::Reset { entries: new } => *entries = new,
+        }
+    }
+}
+
+/// Append-only journal of [`Change`]s that is flushed to disk after every write.
+///
+/// Each session writes its own journal, named after the log it edits and a
+/// ULID, and holds an exclusive lock on it until it is closed. A journal that
+/// can be locked by someone else therefore belongs to a session that ended
+/// without closing it, see [`Orphan`].
+#[derive(Debug)]
+pub(crate) struct Journal {
+    path: PathBuf,
+    file: File,
+}
+
+impl Journal {
+    /// Creates a new journal in `dir` for a log named `stem` whose first
+    /// record is a snapshot of `entries`.
+    ///
+    /// # Errors
+    /// Returns any I/O error raised while creating, locking or syncing the
+    /// file.
+    pub(crate) fn start(dir: &Path, stem: &str, entries: &[Entry]) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{stem}-{}.{JOURNAL_EXTENSION}", Ulid::generate()));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        file.lock()?;
+        let mut journal = Self { path, file };
+        if !entries.is_empty() {
+            journal.append(&Change::Reset {
+                entries: entries.to_vec(),
+            })?;
+        }
+        Ok(journal)
+    }
+
+    /// Appends a change and waits until it has reached the disk.
+    ///
+    /// # Errors
+    /// Returns any serialization or I/O error.
+    pub(crate) fn append(&mut self, change: &Change) -> io::Result<()> {
+        let mut line = serde_json::to_string(change)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.sync_data()
+    }
+
+    /// Returns the location of the journal.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Removes the journal after a clean shutdown.
+    ///
+    /// # Errors
+    /// Returns any I/O error other than the file already being gone.
+    pub(crate) fn close(self) -> io::Result<()> {
+        remove_locked(&self.path, self.file)
+    }
+}
+
+/// A journal left behind by a session that ended without closing it.
+///
+/// It stays locked while the user decides what to do with it, so another
+/// session starting meanwhile does not offer it too.
+#[derive(Debug)]
+pub(crate) struct Orphan {
+    path: PathBuf,
+    file: File,
+}
+
+impl Orphan {
+    /// Finds the newest journal in `dir` for a log named `stem` that no
+    /// running session holds.
+    ///
+    /// The journal of older releases, shared by all sessions, is offered
+    /// before any other.
+    ///
+    /// # Errors
+    /// Returns any I/O error raised while listing `dir`.
+    pub(crate) fn find(dir: &Path, stem: &str) -> io::Result<Option<Self>> {
+        let listing = match fs::read_dir(dir) {
+            Ok(listing) => listing,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut names: Vec<String> = listing
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter(|name| name == LEGACY_JOURNAL || is_journal_of(name, stem))
+            .collect();
+        // ULIDs sort by creation time, so the newest journal comes last.
+        names.sort_by_key(|name| (name != LEGACY_JOURNAL, name.clone()));
+        for name in names.into_iter().rev() {
+            let path = dir.join(name);
+            let Ok(file) = OpenOptions::new().read(true).write(true).open(&path) else {
+                continue;
+            };
+            if file.try_lock().is_ok() {
+                return Ok(Some(Self { path, file }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Replays the journal into the list of entries it describes.
+    ///
+    /// A truncated final record, as left behind by a crash in the middle of a
+    /// write, is ignored.
+    ///
+    /// # Errors
+    /// Returns any I/O error raised while reading the file.
+    pub(crate) fn replay(&self) -> io::Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for line in BufReader::new(&self.file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Change>(&line) {
+                Ok(change) => change.apply(&mut entries),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable journal record: {e}");
+                    break;
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Deletes the journal once its entries were restored or discarded.
+    ///
+    /// # Errors
+    /// Returns any I/O error other than the file already being gone.
+    pub(crate) fn remove(self) -> io::Result<()> {
+        remove_locked(&self.path, self.file)
+    }
+}
+
+/// Returns `true` if `name` is a journal written by [`Journal::start`] for a
+/// log named `stem`.
+fn is_journal_of(name: &str, stem: &str) -> bool {
+    name.strip_suffix(JOURNAL_EXTENSION)
+        .and_then(|n| n.strip_suffix('.'))
+        .and_then(|n| n.rsplit_once('-'))
+        .is_some_and(|(s, id)| s == stem && Ulid::from_string(id).is_ok())
+}
+
+/// Deletes `path` before releasing the lock held through `file`, so no other
+/// session can mistake it for an orphan in between.
+fn remove_locked(path: &Path, file: File) -> io::Result<()> {
+    let result = fs::remove_file(path);
+    drop(file);
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Note, Section};
+    use chrono::Local;
+
+    fn note(text: &str) -> Entry {
+        Entry::Note(Note::new(Local::now(), text))
+    }
+
+    #[test]
+    fn replay_reproduces_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let loaded = vec![note("loaded")];
+        let mut journal = Journal::start(dir.path(), "log", &loaded).unwrap();
+        let changes = [
+            Change::Insert {
+                index: 1,
+                entry: note("second"),
+            },
+            Change::Insert {
+                index: 0,
+                entry: Entry::Section(Section::new("intro")),
+            },
+            Change::Update {
+                index: 1,
+                entry: note("edited"),
+            },
+            Change::Remove { index: 2 },
+            Change::Move { from: 1, to: 0 },
+        ];
+        let mut expected = loaded;
+        for c in &changes {
+            journal.append(c).unwrap();
+            c.clone().apply(&mut expected);
+        }
+        // A journal in use is never offered for recovery.
+        assert!(Orphan::find(dir.path(), "log").unwrap().is_none());
+        // Simulate a crash by dropping the journal without closing it.
+        drop(journal);
+        let orphan = Orphan::find(dir.path(), "log").unwrap().unwrap();
+        assert_eq!(orphan.replay().unwrap(), expected);
+        assert!(Orphan::find(dir.path(), "other").unwrap().is_none());
+        orphan.remove().unwrap();
+        assert!(Orphan::find(dir.path(), "log").unwrap().is_none());
+    }
+
+    #[test]
+    fn close_removes_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::start(dir.path(), "session", &[note("a")]).unwrap();
+        let path = journal.path().to_path_buf();
+        assert!(path.exists());
+        journal.close().unwrap();
+        assert!(!path.exists());
+        assert!(Orphan::find(dir.path(), "session").unwrap().is_none());
+    }
+
+    #[test]
+    fn matches_journals_by_log_name() {
+        let id = Ulid::generate();
+        assert!(is_journal_of(&format!("a-{id}.journal"), "a"));
+        assert!(is_journal_of(&format!("a-b-{id}.journal"), "a-b"));
+        assert!(!is_journal_of(&format!("a-b-{id}.journal"), "a"));
+        assert!(!is_journal_of("a-notanid.journal", "a"));
+        assert!(!is_journal_of(&format!("a-{id}.csv"), "a"));
+    }
+
+    #[test]
+    fn inverse_reverts_change() {
+        let original = vec![note("a"), note("b"), note("c")];
+        let changes = [
+            Change::Insert {
+                index: 1,
+                entry: note("new"),
+            },
+            Change::Update {
+                index: 2,
+                entry: note("edited"),
+            },
+            Change::Remove { index: 0 },
+            Change::Move { from: 0, to: 2 },
+            Change::Reset {
+                entries: Vec::new(),
+            },
+        ];
+        for change in changes {
+            let mut entries = original.clone();
+            let inverse = change.inverse(&entries);
+            change.apply(&mut entries);
+            inverse.apply(&mut entries);
+            assert_eq!(entries, original);
+        }
+    }
+
+    #[test]
+    fn replay_ignores_torn_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut journal = Journal::start(dir.path(), "log", &[]).unwrap();
+        journal
+            .append(&Change::Insert {
+                index: 0,
+                entry: note("kept"),
+            })
+            .unwrap();
+        journal.file.write_all(b"{\"Insert\":{\"ind").unwrap();
+        drop(journal);
+        let orphan = Orphan::find(dir.path(), "log").unwrap().unwrap();
+        assert_eq!(orphan.replay().unwrap().len(), 1);
+    }
+}
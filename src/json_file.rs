@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::csv_file::SessionFile;
+use crate::{AppError, Entry, SessionMetadata};
+
+/// Newest JSON and JSON Lines format version this build reads and writes.
+pub(crate) const JSON_VERSION: u32 = 1;
+
+/// A whole session stored as one JSON document.
+#[derive(Serialize)]
+struct Document<'a> {
+    rtnt: u32,
+    metadata: &'a SessionMetadata,
+    entries: &'a [Entry],
+}
+
+/// Owned counterpart of [`Document`] used when reading.
+#[derive(Deserialize)]
+struct OwnedDocument {
+    rtnt: u32,
+    #[serde(default)]
+    metadata: SessionMetadata,
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+/// First line of a JSON Lines file carrying the format version and metadata.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    rtnt: u32,
+    #[serde(default)]
+    metadata: SessionMetadata,
+}
+
+/// A line of a JSON Lines file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Header(Header),
+    Entry(Entry),
+}
+
+/// Returns `metadata` with the time source recorded for saving.
+fn with_time_source(metadata: &SessionMetadata, time_source: &str) -> SessionMetadata {
+    SessionMetadata {
+        time_source: Some(time_source.to_string()),
+        ..metadata.clone()
+    }
+}
+
+/// Writes `metadata` and `entries` to `out` as a single JSON document.
+///
+/// # Errors
+/// Returns any I/O or serialization error.
+pub(crate) fn write_json(
+    mut out: impl Write,
+    metadata: &SessionMetadata,
+    time_source: &str,
+    entries: &[Entry],
+) -> io::Result<()> {
+    let metadata = with_time_source(metadata, time_source);
+    serde_json::to_writer_pretty(
+        &mut out,
+        &Document {
+            rtnt: JSON_VERSION,
+            metadata: &metadata,
+            entries,
+        },
+    )?;
+    writeln!(out)?;
+    out.flush()
+}
+
+/// Writes `metadata` and `entries` to `out` as JSON Lines.
+///
+/// The first line holds the format version and metadata, followed by one
+/// entry per line so further entries can be appended without rewriting it.
+///
+/// # Errors
+/// Returns any I/O or serialization error.
+pub(crate) fn write_jsonl(
+    mut out: impl Write,
+    metadata: &SessionMetadata,
+    time_source: &str,
+    entries: &[Entry],
+) -> io::Result<()> {
+    let metadata = with_time_source(metadata, time_source);
+    serde_json::to_writer(
+        &mut out,
+        &Header {
+            rtnt: JSON_VERSION,
+            metadata,
+        },
+    )?;
+    writeln!(out)?;
+    for entry in entries {
+        append_jsonl(&mut out, entry)?;
+    }
+    out.flush()
+}
+
+/// Writes a single entry as a line of JSON Lines.
+///
+/// # Errors
+/// Returns any I/O or serialization error.
+pub(crate) fn append_jsonl(mut out: impl Write, entry: &Entry) -> io::Result<()> {
+    serde_json::to_writer(&mut out, entry)?;
+    writeln!(out)
+}
+
+/// Reads a session stored as a single JSON document.
+///
+/// # Errors
+/// Returns [`AppError::UnsupportedVersion`] if the document was written by a
+/// newer version of the format, [`AppError::Parse`] with the line of the first
+/// syntax or type error, or the error from [`AppError::from_io`] if the file
+/// cannot be read.
+pub(crate) fn read_json(path: &Path) -> Result<SessionFile, AppError> {
+    let file = File::open(path).map_err(|e| AppError::from_io(e, path))?;
+    let doc: OwnedDocument =
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| json_error(e, path, 0))?;
+    check_version(doc.rtnt)?;
+    Ok(SessionFile {
+        metadata: doc.metadata,
+        entries: doc.entries,
+    })
+}
+
+/// Reads a session stored as JSON Lines.
+///
+/// The header line is optional so that files built purely by appending
+/// entries can be read too. Blank lines are skipped.
+///
+/// # Errors
+/// Returns the same errors as [`read_json`], with the line number of the
+/// offending entry.
+pub(crate) fn read_jsonl(path: &Path) -> Result<SessionFile, AppError> {
+    let file = File::open(path).map_err(|e| AppError::from_io(e, path))?;
+    let mut session = SessionFile {
+        metadata: SessionMetadata::default(),
+        entries: Vec::new(),
+    };
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| AppError::from_io(e, path))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let number = index as u64 + 1;
+        match serde_json::from_str(&line).map_err(|e| json_error(e, path, number))? {
+            Line::Header(header) if session.entries.is_empty() => {
+                check_version(header.rtnt)?;
+                session.metadata = header.metadata;
+            }
+            Line::Header(_) => {
+                return Err(AppError::Parse {
+                    path: path.to_path_buf(),
+                    line: number,
+                    message: "header must come before any entry".into(),
+                })
+            }
+            Line::Entry(entry) => session.entries.push(entry),
+        }
+    }
+    Ok(session)
+}
+
+fn check_version(found: u32) -> Result<(), AppError> {
+    if found > JSON_VERSION {
+        return Err(AppError::UnsupportedVersion {
+            found,
+            supported: JSON_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Converts a JSON error into an [`AppError`] for `path`.
+///
+/// `line` is the line the JSON text started on, for errors within a single
+/// line of a JSON Lines file, or zero when the text is the whole file.
+fn json_error(error: serde_json::Error, path: &Path, line: u64) -> AppError {
+    if error.is_io() {
+        return AppError::from_io(error.into(), path);
+    }
+    let line = if line == 0 { error.line() as u64 } else { line };
+    AppError::Parse {
+        path: path.to_path_buf(),
+        line,
+        message: if error.is_data() {
+            format!("unexpected content: {error}")
+        } else {
+            error.to_string()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Note, Section};
+    use chrono::{DateTime, Local, TimeZone};
+
+    fn time(h: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 1, h, 0, 0).unwrap()
+    }
+
+    fn sample() -> (SessionMetadata, Vec<Entry>) {
+        let mut metadata = SessionMetadata {
+            title: "Sortie 3".into(),
+            ..SessionMetadata::default()
+        };
+        metadata.set_field("tail", "N123");
+        let mut retimed = Note::new(time(11), "late, \"quoted\"\nline");
+        retimed.retime(time(10));
+        let entries = vec![
+            Entry::Section(Section::new("Climb")),
+            Entry::Note(Note::pending(time(9))),
+            Entry::Note(retimed),
+        ];
+        (metadata, entries)
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.json");
+        let (metadata, entries) = sample();
+        write_json(File::create(&path).unwrap(), &metadata, "System", &entries).unwrap();
+        let file = read_json(&path).unwrap();
+        assert_eq!(file.entries, entries);
+        assert_eq!(file.metadata.fields, metadata.fields);
+        assert_eq!(file.metadata.time_source.as_deref(), Some("System"));
+    }
+
+    #[test]
+    fn jsonl_round_trips_and_accepts_appended_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+        let (metadata, mut entries) = sample();
+        write_jsonl(File::create(&path).unwrap(), &metadata, "System", &entries).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), entries.len() + 1);
+
+        let extra = Entry::Note(Note::new(time(12), "appended"));
+        let mut line = serde_json::to_string(&extra).unwrap();
+        line.push('\n');
+        std::fs::write(&path, text + &line).unwrap();
+        entries.push(extra);
+        let file = read_jsonl(&path).unwrap();
+        assert_eq!(file.entries, entries);
+        assert_eq!(file.metadata.title, "Sortie 3");
+    }
+
+    #[test]
+    fn jsonl_without_header_uses_default_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bare.jsonl");
+        std::fs::write(&path, "{\"Section\":{\"title\":\"Intro\"}}\n\n").unwrap();
+        let file = read_jsonl(&path).unwrap();
+        assert_eq!(file.entries.len(), 1);
+        assert!(file.metadata.title.is_empty());
+    }
+
+    #[test]
+    fn reports_line_of_bad_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.jsonl");
+        std::fs::write(
+            &path,
+            "{\"rtnt\":1}\n{\"Section\":{\"title\":\"Intro\"}}\n{\"Note\":{}}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            read_jsonl(&path),
+            Err(AppError::Parse { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v2.json");
+        std::fs::write(&path, "{\"rtnt\":2,\"entries\":[]}").unwrap();
+        assert!(matches!(
+            read_json(&path),
+            Err(AppError::UnsupportedVersion { found: 2, .. })
+        ));
+    }
+}
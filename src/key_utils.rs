@@ -1,70 +1,324 @@
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MediaKeyCode, ModifierKeyCode};
 
-/// Converts a [`KeyCode`] into a human readable string.
+/// Modifiers that take part in key bindings, in the order they are printed.
+const MODIFIER_NAMES: [(KeyModifiers, &str); 4] = [
+    (KeyModifiers::CONTROL, "Ctrl"),
+    (KeyModifiers::ALT, "Alt"),
+    (KeyModifiers::SHIFT, "Shift"),
+    (KeyModifiers::SUPER, "Super"),
+];
+
+/// Keys with a fixed name, in the spelling [`key_to_string`] prints.
+const NAMED_KEYS: [(KeyCode, &str); 24] = [
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Esc, "Esc"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::BackTab, "BackTab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Char(' '), "Space"),
+    (KeyCode::Null, "Null"),
+    (KeyCode::CapsLock, "CapsLock"),
+    (KeyCode::ScrollLock, "ScrollLock"),
+    (KeyCode::NumLock, "NumLock"),
+    (KeyCode::PrintScreen, "PrintScreen"),
+    (KeyCode::Pause, "Pause"),
+    (KeyCode::Menu, "Menu"),
+    (KeyCode::KeypadBegin, "KeypadBegin"),
+];
+
+/// Alternative spellings accepted by [`string_to_key`] but never printed.
+const KEY_ALIASES: [(KeyCode, &str); 6] = [
+    (KeyCode::Esc, "Escape"),
+    (KeyCode::Enter, "Return"),
+    (KeyCode::Delete, "Del"),
+    (KeyCode::Insert, "Ins"),
+    (KeyCode::PageUp, "PgUp"),
+    (KeyCode::PageDown, "PgDn"),
+];
+
+/// Media keys, prefixed with `Media` so `MediaPause` differs from `Pause`.
+const MEDIA_KEYS: [(MediaKeyCode, &str); 13] = [
+    (MediaKeyCode::Play, "MediaPlay"),
+    (MediaKeyCode::Pause, "MediaPause"),
+    (MediaKeyCode::PlayPause, "MediaPlayPause"),
+    (MediaKeyCode::Reverse, "MediaReverse"),
+    (MediaKeyCode::Stop, "MediaStop"),
+    (MediaKeyCode::FastForward, "MediaFastForward"),
+    (MediaKeyCode::Rewind, "MediaRewind"),
+    (MediaKeyCode::TrackNext, "MediaTrackNext"),
+    (MediaKeyCode::TrackPrevious, "MediaTrackPrevious"),
+    (MediaKeyCode::Record, "MediaRecord"),
+    (MediaKeyCode::LowerVolume, "MediaLowerVolume"),
+    (MediaKeyCode::RaiseVolume, "MediaRaiseVolume"),
+    (MediaKeyCode::MuteVolume, "MediaMuteVolume"),
+];
+
+/// Modifier keys pressed on their own, as reported by some terminals.
+const MODIFIER_KEYS: [(ModifierKeyCode, &str); 14] = [
+    (ModifierKeyCode::LeftShift, "LeftShift"),
+    (ModifierKeyCode::LeftControl, "LeftControl"),
+    (ModifierKeyCode::LeftAlt, "LeftAlt"),
+    (ModifierKeyCode::LeftSuper, "LeftSuper"),
+    (ModifierKeyCode::LeftHyper, "LeftHyper"),
+    (ModifierKeyCode::LeftMeta, "LeftMeta"),
+    (ModifierKeyCode::RightShift, "RightShift"),
+    (ModifierKeyCode::RightControl, "RightControl"),
+    (ModifierKeyCode::RightAlt, "RightAlt"),
+    (ModifierKeyCode::RightSuper, "RightSuper"),
+    (ModifierKeyCode::RightHyper, "RightHyper"),
+    (ModifierKeyCode::RightMeta, "RightMeta"),
+    (ModifierKeyCode::IsoLevel3Shift, "IsoLevel3Shift"),
+    (ModifierKeyCode::IsoLevel5Shift, "IsoLevel5Shift"),
+];
+
+/// A key together with the modifiers that must be held, such as `Ctrl+s`.
+///
+/// Combos are normalized so they compare equal to the events the terminal
+/// reports: `Shift` is folded into the case of character keys, so `Shift+a`
+/// and `A` are the same combo, and is dropped from `BackTab`.
+///
+/// # Example
+/// ```
+/// use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// use real_time_note_taker::KeyCombo;
+///
+/// let save = KeyCombo::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+/// assert!(save.matches(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+/// assert!(!save.matches(&KeyEvent::from(KeyCode::Char('s'))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    /// The key that is pressed.
+    pub code: KeyCode,
+    /// Modifiers held while pressing the key.
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyCombo {
+    /// Creates a normalized combo from a key code and modifiers.
+    #[must_use]
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers = modifiers
+            & (KeyModifiers::CONTROL
+                | KeyModifiers::ALT
+                | KeyModifiers::SHIFT
+                | KeyModifiers::SUPER);
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                let mut upper = c.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(u), None) => KeyCode::Char(u),
+                    _ => KeyCode::Char(c),
+                }
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            other => other,
+        };
+        Self { code, modifiers }
+    }
+
+    /// Creates a combo for a key pressed without modifiers.
+    #[must_use]
+    pub const fn plain(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// Returns `true` if the key event was produced by this combo.
+    #[must_use]
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        *self == Self::from(*event)
+    }
+
+    /// Returns `true` if pressing the combo types text into an input field.
+    #[must_use]
+    pub fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+    }
+}
+
+impl From<KeyCode> for KeyCombo {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+impl From<KeyEvent> for KeyCombo {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl From<KeyCombo> for KeyEvent {
+    fn from(combo: KeyCombo) -> Self {
+        KeyEvent::new(combo.code, combo.modifiers)
+    }
+}
+
+impl PartialEq<KeyCode> for KeyCombo {
+    fn eq(&self, other: &KeyCode) -> bool {
+        *self == Self::from(*other)
+    }
+}
+
+/// Converts a [`KeyCode`] or [`KeyCombo`] into a human readable string.
 ///
 /// This helper is primarily used when serializing or displaying
-/// [`crate::KeyBindings`].
+/// [`crate::KeyBindings`]. Modifiers are written before the key and joined
+/// with `+`, e.g. `Ctrl+s` or `Ctrl+Alt+Enter`. Every key has a name that
+/// [`string_to_key`] parses back to the same key; see it for the grammar.
 ///
 /// # Arguments
-/// * `key` - The key code to convert.
+/// * `key` - The key code or combo to convert.
 ///
 /// # Returns
 /// A string representation of the key.
 ///
 /// # Example
 /// ```
-/// use crossterm::event::KeyCode;
-/// use real_time_note_taker::key_to_string;
+/// use crossterm::event::{KeyCode, KeyModifiers};
+/// use real_time_note_taker::{key_to_string, KeyCombo};
 /// assert_eq!(key_to_string(KeyCode::Enter), "Enter");
+/// assert_eq!(key_to_string(KeyCode::F(1)), "F1");
+/// assert_eq!(
+///     key_to_string(KeyCombo::new(KeyCode::Enter, KeyModifiers::ALT)),
+///     "Alt+Enter"
+/// );
 /// ```
 ///
 /// # See also
 /// [`string_to_key`]
 #[must_use]
-pub fn key_to_string(key: KeyCode) -> String {
-    match key {
-        KeyCode::Char(c) => c.to_string(),
-        KeyCode::Enter => "Enter".to_string(),
-        KeyCode::Esc => "Esc".to_string(),
-        KeyCode::Up => "Up".to_string(),
-        KeyCode::Down => "Down".to_string(),
-        KeyCode::Left => "Left".to_string(),
-        KeyCode::Right => "Right".to_string(),
-        KeyCode::Null => "Null".to_string(),
-        other => format!("{other:?}"),
+pub fn key_to_string(key: impl Into<KeyCombo>) -> String {
+    let key = key.into();
+    let mut out = String::new();
+    for (modifier, name) in MODIFIER_NAMES {
+        if key.modifiers.contains(modifier) {
+            out.push_str(name);
+            out.push('+');
+        }
     }
+    out.push_str(&code_to_string(key.code));
+    out
+}
+
+fn code_to_string(code: KeyCode) -> String {
+    let name = match code {
+        KeyCode::F(n) => return format!("F{n}"),
+        KeyCode::Char(c) if c != ' ' => return c.to_string(),
+        KeyCode::Media(m) => lookup_name(&MEDIA_KEYS, m),
+        KeyCode::Modifier(m) => lookup_name(&MODIFIER_KEYS, m),
+        other => lookup_name(&NAMED_KEYS, other),
+    };
+    name.unwrap_or("Null").to_string()
+}
+
+fn lookup_name<K: Copy + PartialEq>(table: &[(K, &'static str)], key: K) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, n)| *n)
 }
 
-/// Parses a string previously produced by [`key_to_string`] back into a [`KeyCode`].
+fn lookup_key<K: Copy>(table: &[(K, &str)], name: &str) -> Option<K> {
+    table
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(k, _)| *k)
+}
+
+/// Parses a string previously produced by [`key_to_string`] back into a [`KeyCombo`].
+///
+/// A key string is zero or more modifiers followed by a key name, joined
+/// with `+`:
+///
+/// | Part | Names |
+/// |------|-------|
+/// | Modifiers | `Ctrl`, `Alt`, `Shift`, `Super` |
+/// | Characters | the character itself, e.g. `a`, `A`, `?`, `+`; `Space` for the space bar |
+/// | Function keys | `F1` to `F24` (any `F0` to `F255` is accepted) |
+/// | Navigation | `Up`, `Down`, `Left`, `Right`, `Home`, `End`, `PageUp`, `PageDown` |
+/// | Editing | `Enter`, `Esc`, `Tab`, `BackTab`, `Backspace`, `Delete`, `Insert` |
+/// | Other | `Null`, `CapsLock`, `ScrollLock`, `NumLock`, `PrintScreen`, `Pause`, `Menu`, `KeypadBegin` |
+/// | Media | `MediaPlay`, `MediaPause`, `MediaPlayPause`, `MediaStop`, `MediaTrackNext`, ... |
+/// | Modifier keys | `LeftShift`, `RightControl`, `IsoLevel3Shift`, ... |
+///
+/// Modifiers may appear in any order. Modifier and key names are matched
+/// without regard to case, so `alt+ctrl+pageup` parses the same as
+/// `Ctrl+Alt+PageUp`; single characters are case sensitive. The aliases
+/// `Escape`, `Return`, `Del`, `Ins`, `PgUp`, `PgDn` and a literal space are
+/// also accepted. A literal plus key is written as `+`, e.g. `Ctrl++`.
+///
+/// For every key `k`, `string_to_key(&key_to_string(k))` returns `Some(k)`.
 ///
 /// # Arguments
 /// * `s` - The string slice to parse.
 ///
 /// # Returns
-/// The corresponding [`KeyCode`] if recognized, otherwise `None`.
+/// The corresponding [`KeyCombo`] if recognized, otherwise `None`.
 ///
 /// # Example
 /// ```
-/// use crossterm::event::KeyCode;
-/// use real_time_note_taker::string_to_key;
-/// assert_eq!(string_to_key("Esc"), Some(KeyCode::Esc));
+/// use crossterm::event::{KeyCode, KeyModifiers};
+/// use real_time_note_taker::{string_to_key, KeyCombo};
+/// assert_eq!(string_to_key("Esc"), Some(KeyCode::Esc.into()));
+/// assert_eq!(
+///     string_to_key("Ctrl+s"),
+///     Some(KeyCombo::new(KeyCode::Char('s'), KeyModifiers::CONTROL))
+/// );
+/// assert_eq!(string_to_key("Shift+F5"), Some(KeyCombo::new(KeyCode::F(5), KeyModifiers::SHIFT)));
 /// ```
 ///
 /// # See also
 /// [`key_to_string`]
 #[must_use]
-pub fn string_to_key(s: &str) -> Option<KeyCode> {
-    match s {
-        "Enter" => Some(KeyCode::Enter),
-        "Esc" => Some(KeyCode::Esc),
-        "Up" => Some(KeyCode::Up),
-        "Down" => Some(KeyCode::Down),
-        "Left" => Some(KeyCode::Left),
-        "Right" => Some(KeyCode::Right),
-        "Null" => Some(KeyCode::Null),
-        c if c.len() == 1 => c.chars().next().map(KeyCode::Char),
-        _ => None,
+pub fn string_to_key(s: &str) -> Option<KeyCombo> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = s;
+    while let Some(pos) = rest.find('+').filter(|p| *p > 0) {
+        let Some(modifier) = modifier_from_name(&rest[..pos]) else {
+            break;
+        };
+        modifiers |= modifier;
+        rest = &rest[pos + 1..];
+    }
+    string_to_code(rest).map(|code| KeyCombo::new(code, modifiers))
+}
+
+fn modifier_from_name(name: &str) -> Option<KeyModifiers> {
+    MODIFIER_NAMES
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(m, _)| *m)
+}
+
+fn string_to_code(s: &str) -> Option<KeyCode> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    if let Some(n) = s.strip_prefix(['F', 'f']) {
+        if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) {
+            return n.parse().ok().map(KeyCode::F);
+        }
     }
+    lookup_key(&NAMED_KEYS, s)
+        .or_else(|| lookup_key(&KEY_ALIASES, s))
+        .or_else(|| lookup_key(&MEDIA_KEYS, s).map(KeyCode::Media))
+        .or_else(|| lookup_key(&MODIFIER_KEYS, s).map(KeyCode::Modifier))
 }
 
 #[cfg(test)]
@@ -82,10 +336,127 @@ mod tests {
         ];
         for k in keys {
             let s = key_to_string(k);
-            assert_eq!(string_to_key(&s), Some(k));
+            assert_eq!(string_to_key(&s), Some(k.into()));
+        }
+    }
+
+    #[test]
+    fn modifier_combos_round_trip() {
+        let combos = [
+            KeyCombo::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+            KeyCombo::new(KeyCode::Enter, KeyModifiers::ALT),
+            KeyCombo::new(
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            ),
+            KeyCombo::new(KeyCode::Up, KeyModifiers::SHIFT),
+            KeyCombo::new(KeyCode::Char('+'), KeyModifiers::CONTROL),
+            KeyCombo::new(KeyCode::Char('k'), KeyModifiers::SUPER),
+        ];
+        for c in combos {
+            let s = key_to_string(c);
+            assert_eq!(string_to_key(&s), Some(c), "{s}");
+        }
+        assert_eq!(
+            key_to_string(KeyCombo::new(KeyCode::Enter, KeyModifiers::ALT)),
+            "Alt+Enter"
+        );
+        assert_eq!(
+            string_to_key("alt+CTRL+x"),
+            Some(KeyCombo::new(
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(string_to_key("+"), Some(KeyCode::Char('+').into()));
+        assert_eq!(string_to_key("Ctrl+"), None);
+        assert_eq!(string_to_key("Hyper+a"), None);
+    }
+
+    #[test]
+    fn shift_folds_into_characters() {
+        assert_eq!(string_to_key("Shift+a"), Some(KeyCode::Char('A').into()));
+        let event = KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert!(KeyCombo::from(KeyCode::Char('A')).matches(&event));
+        let back_tab = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert!(KeyCombo::from(KeyCode::BackTab).matches(&back_tab));
+    }
+
+    #[test]
+    fn modifiers_must_match() {
+        let ctrl_s = KeyCombo::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert!(!ctrl_s.matches(&KeyEvent::from(KeyCode::Char('s'))));
+        assert!(!KeyCombo::from(KeyCode::Char('s'))
+            .matches(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+        assert!(ctrl_s.matches(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+        assert!(!ctrl_s.is_text());
+        assert!(KeyCombo::from(KeyCode::Char('s')).is_text());
+    }
+
+    fn all_codes() -> Vec<KeyCode> {
+        let mut codes: Vec<KeyCode> = NAMED_KEYS.iter().map(|(k, _)| *k).collect();
+        codes.extend((0..=u8::MAX).map(KeyCode::F));
+        codes.extend(MEDIA_KEYS.iter().map(|(m, _)| KeyCode::Media(*m)));
+        codes.extend(MODIFIER_KEYS.iter().map(|(m, _)| KeyCode::Modifier(*m)));
+        codes.extend(('!'..='~').map(KeyCode::Char));
+        codes.extend(['é', 'ß', 'Ж', '€'].map(KeyCode::Char));
+        codes
+    }
+
+    #[test]
+    fn every_key_round_trips() {
+        let modifier_sets = [
+            KeyModifiers::NONE,
+            KeyModifiers::CONTROL,
+            KeyModifiers::ALT | KeyModifiers::SHIFT,
+            KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER,
+        ];
+        for code in all_codes() {
+            for modifiers in modifier_sets {
+                let combo = KeyCombo::new(code, modifiers);
+                let s = key_to_string(combo);
+                assert_eq!(string_to_key(&s), Some(combo), "{s}");
+            }
         }
     }
 
+    #[test]
+    fn key_names_are_unique() {
+        let mut names: Vec<String> = all_codes().into_iter().map(key_to_string).collect();
+        let total = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), total);
+    }
+
+    #[test]
+    fn named_keys_print_readably() {
+        assert_eq!(key_to_string(KeyCode::F(24)), "F24");
+        assert_eq!(key_to_string(KeyCode::PageUp), "PageUp");
+        assert_eq!(key_to_string(KeyCode::BackTab), "BackTab");
+        assert_eq!(key_to_string(KeyCode::Char(' ')), "Space");
+        assert_eq!(
+            key_to_string(KeyCode::Media(MediaKeyCode::Pause)),
+            "MediaPause"
+        );
+        assert_eq!(
+            key_to_string(KeyCombo::new(KeyCode::Delete, KeyModifiers::CONTROL)),
+            "Ctrl+Delete"
+        );
+    }
+
+    #[test]
+    fn aliases_and_case_are_accepted() {
+        assert_eq!(string_to_key("escape"), Some(KeyCode::Esc.into()));
+        assert_eq!(string_to_key("PgDn"), Some(KeyCode::PageDown.into()));
+        assert_eq!(string_to_key("f12"), Some(KeyCode::F(12).into()));
+        assert_eq!(string_to_key(" "), Some(KeyCode::Char(' ').into()));
+        assert_eq!(string_to_key("ctrl+space"), string_to_key("Ctrl+Space"));
+        assert_eq!(string_to_key("F"), Some(KeyCode::Char('F').into()));
+        assert_eq!(string_to_key("F256"), None);
+        assert_eq!(string_to_key("F+1"), None);
+    }
+
     #[test]
     fn string_to_key_invalid() {
         assert_eq!(string_to_key("invalid"), None);
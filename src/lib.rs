@@ -17,14 +17,42 @@
 //! ```
 
 mod app;
+mod clock;
+mod csv_file;
+mod export;
+mod file_format;
+mod file_lock;
+mod gps;
+mod history;
+mod journal;
+mod json_file;
 mod key_utils;
+mod ntp;
+mod remote;
+mod status;
+mod stream;
 mod theme;
 mod ui;
+mod zone;
 
-pub use app::{Action, App, AppError, Entry, InputMode, KeyBindings, Note, Section};
-pub use key_utils::{key_to_string, string_to_key};
+pub use app::{
+    Action, App, AppError, Entry, InputMode, KeyBindings, Note, Section, SessionMetadata,
+};
+pub use clock::{
+    clock_drift, interpolate_hacks, ClockHealth, ClockSource, HackTime, SimulatedClock,
+    SystemClock, TimeHack,
+};
+pub use export::ExportFormat;
+pub use file_format::FileFormat;
+pub use gps::GpsClock;
+pub use key_utils::{key_to_string, string_to_key, KeyCombo};
+pub use ntp::NtpClock;
+pub use remote::{RemoteCommand, RemoteEvent};
+pub use status::{StatusKind, StatusMessage};
 use std::io;
 pub use theme::{Theme, ThemeName};
+pub use ulid::Ulid;
+pub use zone::DisplayZone;
 
 /// Runs the real-time note taking application until the user exits.
 ///
@@ -1,13 +1,99 @@
 #![warn(clippy::pedantic)]
-use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use chrono::{DateTime, Local, NaiveTime};
+use clap::{error::ErrorKind, ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Shell};
 use log::LevelFilter;
-use real_time_note_taker::{run, App, KeyBindings};
+use real_time_note_taker::{
+    run, App, AppError, DisplayZone, Entry, ExportFormat, FileFormat, GpsClock, KeyBindings, Note,
+    NtpClock, Section, StatusKind,
+};
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 #[derive(Subcommand)]
 enum Commands {
     /// Generate shell completion script for the specified shell
     Completions { shell: Shell },
+    /// Export a saved log as a report or in another file format
+    Export {
+        /// Saved log to read
+        input: PathBuf,
+        /// Write the document to this file instead of standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Format to write [default: from the output extension, else md]
+        #[arg(short, long, value_enum)]
+        format: Option<Format>,
+    },
+    /// Append a note or section to a log without opening the UI
+    Add {
+        /// Log to append to; it is created if it does not exist
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Time of the note as RFC 3339 or HH:MM:SS[.fff] today in --zone [default: now]
+        #[arg(long, value_parser = parse_at, conflicts_with = "section")]
+        at: Option<At>,
+        /// Add a section titled TEXT instead of a note
+        #[arg(long)]
+        section: bool,
+        /// Text of the note or section title
+        text: String,
+    },
+    /// Convert a saved log to the format given by the output extension
+    Convert {
+        /// Saved log to read
+        input: PathBuf,
+        /// File to write
+        output: PathBuf,
+    },
+}
+
+/// Formats the `export` and `convert` commands can write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Markdown report
+    #[value(alias = "markdown")]
+    Md,
+    /// Self-contained HTML report
+    #[value(alias = "htm")]
+    Html,
+    /// JSON document
+    Json,
+    /// JSON Lines, one entry per line
+    Jsonl,
+    /// CSV, the default save format
+    Csv,
+}
+
+impl Format {
+    /// Returns the format named by the extension of `path`, if any.
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        Self::from_str(ext, true).ok()
+    }
+
+    /// Writes `app` in this format to `output`, or standard output if `None`.
+    fn write(self, app: &App, output: Option<&Path>) -> Result<(), AppError> {
+        let save = match self {
+            Self::Md => Err(ExportFormat::Markdown),
+            Self::Html => Err(ExportFormat::Html),
+            Self::Json => Ok(FileFormat::Json),
+            Self::Jsonl => Ok(FileFormat::JsonLines),
+            Self::Csv => Ok(FileFormat::Csv),
+        };
+        match (save, output) {
+            (Ok(format), Some(path)) => app.save_as(path, format),
+            (Err(format), Some(path)) => app.export_to_file(path, format),
+            (Ok(format), None) => Ok(app.write_session(io::stdout().lock(), format)?),
+            (Err(format), None) => {
+                let mut out = io::stdout().lock();
+                out.write_all(app.export(format).as_bytes())?;
+                Ok(out.flush()?)
+            }
+        }
+    }
 }
 
 #[derive(Parser)]
@@ -15,15 +101,44 @@ enum Commands {
 struct Cli {
     /// Load notes from this file and save them on exit
     #[arg(long)]
-    file: Option<std::path::PathBuf>,
+    file: Option<PathBuf>,
 
     /// Override the default directory for saved files
     #[arg(long)]
-    save_dir: Option<std::path::PathBuf>,
+    save_dir: Option<PathBuf>,
 
     /// Path to an alternative key binding configuration file
     #[arg(long)]
-    config: Option<std::path::PathBuf>,
+    config: Option<PathBuf>,
+
+    /// Accept commands from other programs on a Unix domain socket at this path
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH")]
+    listen: Option<PathBuf>,
+
+    /// Turn UDP datagrams received on this address into timestamp markers
+    #[arg(long, value_name = "ADDR")]
+    listen_udp: Option<SocketAddr>,
+
+    /// Take time from NMEA sentences on a serial device, file or udp:ADDR
+    #[arg(long, value_name = "SOURCE")]
+    gps: Option<String>,
+
+    /// Take time from a network time server, such as pool.ntp.org
+    #[arg(long, value_name = "SERVER", conflicts_with = "gps")]
+    ntp: Option<String>,
+
+    /// Stream changes to the log as JSON Lines to this file, FIFO or /dev/fd/N
+    #[arg(long, value_name = "PATH")]
+    stream: Option<PathBuf>,
+
+    /// Show and enter times in this zone: local, UTC or a name such as Europe/Paris
+    #[arg(long, global = true, value_name = "ZONE", default_value_t)]
+    zone: DisplayZone,
+
+    /// Show a second clock in this zone in the status line
+    #[arg(long, value_name = "ZONE")]
+    second_zone: Option<DisplayZone>,
 
     /// Increase logging verbosity (-v, -vv, etc.)
     #[arg(short, long, action = ArgAction::Count)]
@@ -33,22 +148,97 @@ struct Cli {
     command: Option<Commands>,
 }
 
-fn main() -> std::io::Result<()> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
     init_logger(cli.verbose);
+    match run_cli(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            exit_code(&e)
+        }
+    }
+}
 
-    if let Some(Commands::Completions { shell }) = cli.command {
-        let mut cmd = Cli::command();
-        generate(shell, &mut cmd, "rtnt", &mut std::io::stdout());
-        return Ok(());
+/// Maps an error to a `sysexits.h` style exit code so scripts can tell
+/// missing input from malformed input.
+fn exit_code(error: &AppError) -> ExitCode {
+    ExitCode::from(match error {
+        AppError::Parse { .. } | AppError::UnsupportedVersion { .. } => 65,
+        AppError::NotFound(_) => 66,
+        AppError::UnknownFormat(_) => 64,
+        AppError::Io(_) => 74,
+        AppError::PermissionDenied(_) => 77,
+    })
+}
+
+fn run_cli(cli: Cli) -> Result<(), AppError> {
+    match cli.command {
+        Some(Commands::Completions { shell }) => {
+            let mut cmd = Cli::command();
+            generate(shell, &mut cmd, "rtnt", &mut std::io::stdout());
+            return Ok(());
+        }
+        Some(Commands::Export {
+            input,
+            output,
+            format,
+        }) => {
+            let format = format
+                .or_else(|| output.as_deref().and_then(Format::from_path))
+                .unwrap_or(Format::Md);
+            let mut app = App::load_from_file(input)?;
+            app.display_zone = cli.zone;
+            return format.write(&app, output.as_deref());
+        }
+        Some(Commands::Add {
+            file,
+            at,
+            section,
+            text,
+        }) => {
+            let entry = if section {
+                Entry::Section(Section::new(text))
+            } else {
+                let timestamp = at.map_or_else(Local::now, |at| at.resolve(cli.zone));
+                Entry::Note(Note::new(timestamp, text))
+            };
+            return App::append_to_file(file, entry);
+        }
+        Some(Commands::Convert { input, output }) => {
+            let format = Format::from_path(&output)
+                .ok_or_else(|| AppError::UnknownFormat(output.clone()))?;
+            let mut app = App::load_from_file(input)?;
+            app.display_zone = cli.zone;
+            return format.write(&app, Some(&output));
+        }
+        None => {}
     }
 
-    let mut app = if let Some(ref file) = cli.file {
-        App::load_from_file(file).unwrap_or_else(|_| App::new())
-    } else {
-        App::new()
+    let mut app = match cli.file {
+        Some(ref file) => match App::load_from_file(file) {
+            Ok(mut app) => {
+                app.set_status(
+                    StatusKind::Info,
+                    format!(
+                        "Loaded {} entries from {}",
+                        app.entries.len(),
+                        file.display()
+                    ),
+                );
+                app
+            }
+            // The file is created when the session ends.
+            Err(AppError::NotFound(_)) => App::new(),
+            // Saving on exit would overwrite whatever could not be read.
+            Err(e) => return Err(e),
+        },
+        None => App::new(),
     };
 
+    app.display_zone = cli.zone;
+    app.second_zone = cli.second_zone;
+
     if let Some(save_dir) = cli.save_dir {
         std::fs::create_dir_all(&save_dir)?;
         app.save_dir = save_dir;
@@ -59,13 +249,97 @@ fn main() -> std::io::Result<()> {
         app.set_keybindings(keys);
     }
 
-    app = run(app)?;
     if let Some(file) = cli.file {
-        app.save_to_file(file)?;
+        app.save_on_quit(file);
+    }
+
+    #[cfg(unix)]
+    if let Some(path) = cli.listen {
+        app.listen_socket(&path)?;
+    }
+
+    if let Some(addr) = cli.listen_udp {
+        app.listen_udp(addr)?;
+    }
+
+    set_clock_source(&mut app, cli.gps, cli.ntp)?;
+
+    if let Some(path) = cli.stream {
+        app.stream_to(path)?;
+    }
+
+    if let Err(e) = app.open_journal() {
+        app.set_status(
+            StatusKind::Error,
+            format!("Crash recovery journal unavailable: {e}"),
+        );
+    }
+
+    app = run(app)?;
+    app.close_journal()?;
+    Ok(())
+}
+
+/// Installs the clock source chosen with `--gps` or `--ntp`, if any.
+fn set_clock_source(app: &mut App, gps: Option<String>, ntp: Option<String>) -> io::Result<()> {
+    if let Some(source) = gps {
+        let clock = match source.strip_prefix("udp:") {
+            Some(addr) => GpsClock::listen_udp(addr),
+            None => GpsClock::open(&source),
+        }
+        .map_err(|e| io::Error::new(e.kind(), format!("GPS source {source}: {e}")))?;
+        app.set_clock(clock);
+    }
+    if let Some(server) = ntp {
+        let clock = NtpClock::connect(&server)
+            .map_err(|e| io::Error::new(e.kind(), format!("NTP server {server}: {e}")))?;
+        app.set_clock(clock);
     }
     Ok(())
 }
 
+/// The `--at` time of `rtnt add`, before the zone it is given in is known.
+#[derive(Clone)]
+enum At {
+    /// A timestamp with its offset.
+    Instant(DateTime<Local>),
+    /// A time of day, today.
+    TimeOfDay(NaiveTime),
+}
+
+impl At {
+    /// Returns the instant meant, reading a time of day in `zone`.
+    ///
+    /// Exits with a usage error if the time is skipped today by a change to
+    /// summer time.
+    fn resolve(self, zone: DisplayZone) -> DateTime<Local> {
+        match self {
+            Self::Instant(time) => time,
+            Self::TimeOfDay(time) => {
+                let today = zone.wall_clock(Local::now()).date();
+                zone.from_wall_clock(today.and_time(time))
+                    .unwrap_or_else(|| {
+                        let message = format!(
+                            "invalid value for '--at <AT>': `{time}` does not exist today in the {zone} time zone"
+                        );
+                        Cli::command().error(ErrorKind::ValueValidation, message).exit()
+                    })
+            }
+        }
+    }
+}
+
+/// Parses the `--at` time of `rtnt add`.
+fn parse_at(input: &str) -> Result<At, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(At::Instant(time.with_timezone(&Local)));
+    }
+    NaiveTime::parse_from_str(input, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M:%S"))
+        .map(At::TimeOfDay)
+        .map_err(|_| format!("`{input}` is not an RFC 3339 timestamp or HH:MM:SS time"))
+}
+
 fn init_logger(verbosity: u8) {
     let level = match verbosity {
         0 => LevelFilter::Warn,
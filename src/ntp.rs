@@ -0,0 +1,327 @@
+use chrono::{DateTime, Local, Utc};
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::time::{Duration, Instant};
+
+use crate::clock::{ClockHealth, ClockSource};
+
+/// Port NTP servers answer on unless another is given.
+const NTP_PORT: u16 = 123;
+
+/// How often the server is asked for the time once it has answered. Public
+/// servers ask clients not to poll more often than this.
+const POLL_INTERVAL: Duration = Duration::from_secs(64);
+
+/// How soon the server is asked again after a query failed.
+const RETRY_INTERVAL: Duration = Duration::from_secs(8);
+
+/// How long to wait for the server to answer a query.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long the last answer is trusted before falling back to the system
+/// clock, enough for a few queries in a row to go unanswered.
+const STALE_AFTER: Duration = Duration::from_secs(4 * POLL_INTERVAL.as_secs());
+
+/// Seconds from the NTP epoch, 1900-01-01, to the Unix epoch.
+const UNIX_EPOCH_OFFSET: i64 = 2_208_988_800;
+
+/// Length of an SNTP packet without extension fields.
+const PACKET_LEN: usize = 48;
+
+/// A time reported by the server and when its answer arrived.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    time: DateTime<Utc>,
+    received: Instant,
+}
+
+/// A clock set from a network time server with the Simple Network Time
+/// Protocol (SNTP, RFC 4330).
+///
+/// The server is queried by a background thread about once a minute.
+/// Between answers the clock advances from the last one on the monotonic
+/// system timer, so the system clock being stepped does not affect it. When
+/// the server has not answered for a few minutes the system clock is used
+/// instead and [`health`](ClockSource::health) reports the time as stale.
+#[derive(Debug, Clone)]
+pub struct NtpClock {
+    last_sample: Arc<Mutex<Option<Sample>>>,
+    server: SocketAddr,
+}
+
+impl NtpClock {
+    /// Starts asking `server` for the time.
+    ///
+    /// `server` is a host name or address, such as `pool.ntp.org` or
+    /// `192.168.1.10:123`; port 123 is used if none is given.
+    ///
+    /// # Errors
+    /// Returns an error if `server` cannot be resolved or no socket can be
+    /// bound to reach it.
+    pub fn connect(server: &str) -> io::Result<Self> {
+        let server = resolve(server)?;
+        let local: SocketAddr = if server.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(server)?;
+        socket.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+        let clock = Self {
+            last_sample: Arc::new(Mutex::new(None)),
+            server,
+        };
+        let last_sample = Arc::downgrade(&clock.last_sample);
+        std::thread::spawn(move || poll(&socket, &last_sample));
+        Ok(clock)
+    }
+
+    /// Returns the address of the server being queried.
+    #[must_use]
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// Returns the last answer if it is recent enough to be used.
+    fn fresh_sample(&self) -> Result<Sample, ClockHealth> {
+        let last = *self
+            .last_sample
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match last {
+            Some(sample) if sample.received.elapsed() < STALE_AFTER => Ok(sample),
+            Some(sample) => Err(ClockHealth::Stale(format!(
+                "no NTP answer for {} s",
+                sample.received.elapsed().as_secs()
+            ))),
+            None => Err(ClockHealth::Stale("waiting for NTP server".into())),
+        }
+    }
+}
+
+impl ClockSource for NtpClock {
+    fn name(&self) -> &'static str {
+        "NTP"
+    }
+
+    fn now(&self) -> DateTime<Local> {
+        match self.fresh_sample() {
+            Ok(sample) => {
+                let elapsed =
+                    chrono::Duration::from_std(sample.received.elapsed()).unwrap_or_default();
+                (sample.time + elapsed).with_timezone(&Local)
+            }
+            Err(_) => Local::now(),
+        }
+    }
+
+    fn health(&self) -> ClockHealth {
+        self.fresh_sample().err().unwrap_or(ClockHealth::Good)
+    }
+}
+
+/// Returns the first address of `server`, using the NTP port if it has none.
+fn resolve(server: &str) -> io::Result<SocketAddr> {
+    let mut addrs = match server.to_socket_addrs() {
+        Ok(addrs) => addrs,
+        Err(_) => (server, NTP_PORT).to_socket_addrs()?,
+    };
+    addrs
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{server} has no address")))
+}
+
+/// Queries the server through `socket` until the clock is dropped.
+fn poll(socket: &UdpSocket, last_sample: &Weak<Mutex<Option<Sample>>>) {
+    loop {
+        let sample = query(socket);
+        let Some(last_sample) = last_sample.upgrade() else {
+            return;
+        };
+        let wait = match sample {
+            Ok(sample) => {
+                *last_sample.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                POLL_INTERVAL
+            }
+            Err(e) => {
+                log::warn!("NTP query failed: {e}");
+                RETRY_INTERVAL
+            }
+        };
+        drop(last_sample);
+        std::thread::sleep(wait);
+    }
+}
+
+/// Asks the server for the time once.
+///
+/// The answer is corrected by half the network delay, which is the round
+/// trip less the time the server took to reply.
+fn query(socket: &UdpSocket) -> io::Result<Sample> {
+    let mut request = [0; PACKET_LEN];
+    // No leap second warning, version 4, client mode.
+    request[0] = 0b00_100_011;
+    // The server echoes the transmit time, which tells its answer apart
+    // from stray or forged packets.
+    let origin = to_ntp(Utc::now());
+    request[40..48].copy_from_slice(&origin.to_be_bytes());
+    let sent = Instant::now();
+    socket.send(&request)?;
+    let mut response = [0; 1024];
+    let len = socket.recv(&mut response)?;
+    let received = Instant::now();
+    let (receive, transmit) = parse_response(&response[..len], origin)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let round_trip = chrono::Duration::from_std(received - sent).unwrap_or_default();
+    let delay = (round_trip - (transmit - receive)).max(chrono::Duration::zero());
+    Ok(Sample {
+        time: transmit + delay / 2,
+        received,
+    })
+}
+
+/// Extracts the times the server received the request and sent its answer.
+fn parse_response(packet: &[u8], origin: u64) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    if packet.len() < PACKET_LEN {
+        return Err(format!("answer of {} bytes is too short", packet.len()));
+    }
+    let timestamp = |at: usize| {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&packet[at..at + 8]);
+        u64::from_be_bytes(bytes)
+    };
+    if packet[0] & 0b111 != 4 {
+        return Err("answer is not from a server".into());
+    }
+    if packet[1] == 0 {
+        let code = String::from_utf8_lossy(&packet[12..16]);
+        return Err(format!("server refused the query with code {code}"));
+    }
+    if packet[0] >> 6 == 3 {
+        return Err("server is not synchronized".into());
+    }
+    if timestamp(24) != origin {
+        return Err("answer does not match the query".into());
+    }
+    let time = |at: usize| from_ntp(timestamp(at)).ok_or("answer has an invalid time");
+    Ok((time(32)?, time(40)?))
+}
+
+/// Converts a time to an NTP timestamp: seconds since 1900 in the upper 32
+/// bits and the fraction of a second in the lower 32.
+fn to_ntp(time: DateTime<Utc>) -> u64 {
+    // Only the low 32 bits of the seconds are sent, which wrap in 2036.
+    #[allow(clippy::cast_sign_loss)]
+    let seconds = (time.timestamp() + UNIX_EPOCH_OFFSET) as u64 & 0xFFFF_FFFF;
+    let fraction = (u64::from(time.timestamp_subsec_nanos()) << 32) / 1_000_000_000;
+    seconds << 32 | fraction
+}
+
+/// Converts an NTP timestamp to a time.
+///
+/// Seconds with the top bit clear are taken to be after the counter wraps
+/// in 2036, as RFC 4330 suggests, which covers 1968 to 2104.
+fn from_ntp(timestamp: u64) -> Option<DateTime<Utc>> {
+    let mut seconds = timestamp >> 32;
+    if seconds & 0x8000_0000 == 0 {
+        seconds += 1 << 32;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let nanos = (((timestamp & 0xFFFF_FFFF) * 1_000_000_000) >> 32) as u32;
+    DateTime::from_timestamp(i64::try_from(seconds).ok()? - UNIX_EPOCH_OFFSET, nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn reference() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap() + chrono::Duration::milliseconds(250)
+    }
+
+    /// Builds the answer of a server whose clock reads `time`.
+    fn answer(request: &[u8], time: DateTime<Utc>) -> [u8; PACKET_LEN] {
+        let mut packet = [0; PACKET_LEN];
+        packet[0] = 0b00_100_100;
+        packet[1] = 1;
+        packet[24..32].copy_from_slice(&request[40..48]);
+        packet[32..40].copy_from_slice(&to_ntp(time).to_be_bytes());
+        packet[40..48].copy_from_slice(&to_ntp(time).to_be_bytes());
+        packet
+    }
+
+    #[test]
+    fn converts_ntp_timestamps() {
+        let time = reference();
+        let converted = from_ntp(to_ntp(time)).unwrap();
+        assert!((converted - time).abs() < chrono::Duration::microseconds(1));
+        let later = Utc.with_ymd_and_hms(2040, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(from_ntp(to_ntp(later)), Some(later));
+    }
+
+    #[test]
+    fn rejects_unusable_answers() {
+        let mut request = [0; PACKET_LEN];
+        request[40..48].copy_from_slice(&to_ntp(reference()).to_be_bytes());
+        let origin = to_ntp(reference());
+        let good = answer(&request, reference());
+        assert!(parse_response(&good, origin).is_ok());
+        assert!(parse_response(&good[..40], origin).is_err());
+        assert!(parse_response(&good, origin + 1).is_err());
+        let mut refused = good;
+        refused[1] = 0;
+        refused[12..16].copy_from_slice(b"RATE");
+        assert_eq!(
+            parse_response(&refused, origin),
+            Err("server refused the query with code RATE".into())
+        );
+        let mut unsynchronized = good;
+        unsynchronized[0] |= 0b1100_0000;
+        assert!(parse_response(&unsynchronized, origin).is_err());
+    }
+
+    #[test]
+    fn follows_the_server() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut request = [0; PACKET_LEN];
+            while let Ok((_, peer)) = server.recv_from(&mut request) {
+                let _ = server.send_to(&answer(&request, reference()), peer);
+            }
+        });
+        let clock = NtpClock::connect(&addr.to_string()).unwrap();
+        assert_eq!(clock.server(), addr);
+        let start = Instant::now();
+        while !clock.health().is_good() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(clock.health(), ClockHealth::Good);
+        let offset = clock.now().with_timezone(&Utc) - reference();
+        assert!(offset >= chrono::Duration::zero() && offset < chrono::Duration::seconds(1));
+    }
+
+    #[test]
+    fn falls_back_to_system_time_until_answered() {
+        let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let clock = NtpClock::connect(&silent.local_addr().unwrap().to_string()).unwrap();
+        assert_eq!(
+            clock.health(),
+            ClockHealth::Stale("waiting for NTP server".into())
+        );
+        let drift = Local::now() - clock.now();
+        assert!(drift.num_seconds().abs() < 1);
+        let received = Instant::now().checked_sub(STALE_AFTER).unwrap();
+        *clock.last_sample.lock().unwrap() = Some(Sample {
+            time: reference(),
+            received,
+        });
+        assert!(
+            matches!(clock.health(), ClockHealth::Stale(m) if m.starts_with("no NTP answer for 256"))
+        );
+        assert!(NtpClock::connect("no such host.invalid").is_err());
+    }
+}
@@ -0,0 +1,378 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+use crate::HackTime;
+
+/// Source recorded on entries created through the socket when the client
+/// does not name itself.
+#[cfg(unix)]
+const SOCKET_SOURCE: &str = "socket";
+
+/// Source recorded on entries created from UDP datagrams.
+const UDP_SOURCE: &str = "udp";
+
+/// Largest datagram accepted; longer ones are truncated.
+const MAX_DATAGRAM: usize = 4096;
+
+/// How long to wait before receiving again after a socket error.
+const RECV_RETRY: Duration = Duration::from_secs(1);
+
+/// An action requested by another program.
+///
+/// Commands are sent as one JSON object per line with the command name in
+/// the `cmd` field:
+///
+/// ```json
+/// {"cmd": "note", "text": "Gear up", "source": "sim"}
+/// {"cmd": "section", "title": "Approach"}
+/// {"cmd": "mark"}
+/// {"cmd": "time_hack", "time": "13:45:00.250"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum RemoteCommand {
+    /// Add a described note, stamped when the command arrived unless `at` is given.
+    Note {
+        /// Text of the note.
+        text: String,
+        /// Time of the event if it is not the time of arrival.
+        #[serde(default)]
+        at: Option<DateTime<Local>>,
+    },
+    /// Add a section at the end of the log.
+    Section {
+        /// Section title.
+        title: String,
+    },
+    /// Capture a pending timestamp for the operator to describe later.
+    Mark {
+        /// Time of the event if it is not the time of arrival.
+        #[serde(default)]
+        at: Option<DateTime<Local>>,
+    },
+    /// Set the clock to `time` as of the moment the command arrived.
+    TimeHack {
+        /// Reference time in any form the time hack prompt accepts.
+        time: HackTime,
+    },
+}
+
+/// A [`RemoteCommand`] together with where and when it was received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEvent {
+    /// The requested action.
+    pub command: RemoteCommand,
+    /// Name of the sending program, recorded on the entries it creates.
+    pub source: String,
+    /// When the command arrived, so delays before it is applied do not shift
+    /// its timestamp.
+    pub received: Instant,
+}
+
+/// One line of the socket protocol: a command and an optional source name.
+#[derive(Deserialize)]
+struct Request {
+    #[serde(flatten)]
+    command: RemoteCommand,
+    #[serde(default)]
+    source: Option<String>,
+}
+
+/// Queue of events received by background listeners, drained by the UI loop.
+#[derive(Debug)]
+pub(crate) struct Inbox {
+    sender: Sender<RemoteEvent>,
+    receiver: Receiver<RemoteEvent>,
+    /// Socket files to remove when the session ends.
+    #[cfg(unix)]
+    sockets: Vec<PathBuf>,
+}
+
+impl Default for Inbox {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            #[cfg(unix)]
+            sockets: Vec::new(),
+        }
+    }
+}
+
+impl Inbox {
+    /// Returns the next received event without waiting.
+    pub(crate) fn try_next(&self) -> Option<RemoteEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Starts accepting connections on a Unix domain socket at `path`.
+    ///
+    /// A stale socket file left behind by a previous session is replaced, but
+    /// a socket another process is still listening on is not, and neither is
+    /// anything at `path` that is not a socket.
+    #[cfg(unix)]
+    pub(crate) fn listen_socket(&mut self, path: &Path) -> io::Result<()> {
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => {
+                if UnixStream::connect(path).is_ok() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("{} is already in use", path.display()),
+                    ));
+                }
+                std::fs::remove_file(path)?;
+            }
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ));
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let listener = UnixListener::bind(path)?;
+        self.sockets.push(path.to_path_buf());
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let sender = sender.clone();
+                std::thread::spawn(move || serve(stream, &sender));
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Inbox {
+    /// Starts receiving event markers as UDP datagrams on `addr`.
+    ///
+    /// An empty datagram marks the time it arrived, plain text adds a note
+    /// with that text, and a JSON object is read as a [`RemoteCommand`] like
+    /// a line sent to the Unix socket. Only notes and marks are accepted, as
+    /// anyone who can reach the port can send them.
+    ///
+    /// # Returns
+    /// The address actually bound, which tells the port chosen for port 0.
+    pub(crate) fn listen_udp(&mut self, addr: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+        let socket = UdpSocket::bind(addr)?;
+        let local = socket.local_addr()?;
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0; MAX_DATAGRAM];
+            loop {
+                let (len, peer) = match socket.recv_from(&mut buf) {
+                    Ok(received) => received,
+                    Err(e) => {
+                        log::warn!("Failed to receive datagram: {e}");
+                        std::thread::sleep(RECV_RETRY);
+                        continue;
+                    }
+                };
+                let received = Instant::now();
+                let text = String::from_utf8_lossy(&buf[..len]);
+                match datagram_event(text.trim(), received) {
+                    Ok(event) => {
+                        if sender.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => log::warn!("Ignoring malformed datagram from {peer}: {e}"),
+                }
+            }
+        });
+        Ok(local)
+    }
+}
+
+/// Interprets the trimmed contents of a UDP datagram.
+fn datagram_event(text: &str, received: Instant) -> Result<RemoteEvent, String> {
+    let (command, source) = if text.starts_with('{') {
+        let request: Request = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        match request.command {
+            RemoteCommand::Note { .. } | RemoteCommand::Mark { .. } => {}
+            RemoteCommand::Section { .. } | RemoteCommand::TimeHack { .. } => {
+                return Err("only notes and marks are accepted over UDP".into());
+            }
+        }
+        (request.command, request.source)
+    } else if text.is_empty() {
+        (RemoteCommand::Mark { at: None }, None)
+    } else {
+        let command = RemoteCommand::Note {
+            text: text.to_string(),
+            at: None,
+        };
+        (command, None)
+    };
+    Ok(RemoteEvent {
+        command,
+        source: source.unwrap_or_else(|| UDP_SOURCE.into()),
+        received,
+    })
+}
+
+#[cfg(unix)]
+impl Drop for Inbox {
+    fn drop(&mut self) {
+        for path in &self.sockets {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Reads commands from one client until it disconnects.
+///
+/// Every line is answered with `{"ok":true}` once the command is queued, or
+/// `{"ok":false,"error":"..."}` if it could not be understood.
+#[cfg(unix)]
+fn serve(stream: UnixStream, sender: &Sender<RemoteEvent>) {
+    let Ok(mut replies) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        let received = Instant::now();
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let event = RemoteEvent {
+                    command: request.command,
+                    source: request.source.unwrap_or_else(|| SOCKET_SOURCE.into()),
+                    received,
+                };
+                if sender.send(event).is_err() {
+                    break;
+                }
+                serde_json::json!({ "ok": true })
+            }
+            Err(e) => {
+                log::warn!("Ignoring malformed remote command: {e}");
+                serde_json::json!({ "ok": false, "error": e.to_string() })
+            }
+        };
+        if writeln!(replies, "{reply}").is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next_event(inbox: &Inbox) -> RemoteEvent {
+        inbox
+            .receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("no event received")
+    }
+
+    #[test]
+    fn datagrams_become_marks_or_notes() {
+        let mut inbox = Inbox::default();
+        let addr = inbox.listen_udp("127.0.0.1:0").unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        for packet in [
+            "",
+            " Button 2\n",
+            "{\"cmd\":\"note\",\"text\":\"Run 4\",\"source\":\"rig\"}",
+            "{\"cmd\":\"time_hack\",\"time\":\"13:45:00\"}",
+            "{\"cmd\":\"section\",\"title\":\"Run 5\"}",
+            "{bad",
+        ] {
+            client.send_to(packet.as_bytes(), addr).unwrap();
+        }
+        let mark = next_event(&inbox);
+        assert_eq!(mark.command, RemoteCommand::Mark { at: None });
+        assert_eq!(mark.source, UDP_SOURCE);
+        let note = next_event(&inbox);
+        assert!(matches!(note.command, RemoteCommand::Note { text, .. } if text == "Button 2"));
+        let json = next_event(&inbox);
+        assert_eq!(json.source, "rig");
+        assert!(inbox
+            .receiver
+            .recv_timeout(Duration::from_millis(200))
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_queues_commands_and_replies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rtnt.sock");
+        let mut inbox = Inbox::default();
+        inbox.listen_socket(&path).unwrap();
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        writeln!(
+            client,
+            "{{\"cmd\":\"note\",\"text\":\"Gear up\",\"source\":\"sim\"}}"
+        )
+        .unwrap();
+        writeln!(client, "{{\"cmd\":\"launch\"}}").unwrap();
+        writeln!(
+            client,
+            "{{\"cmd\":\"time_hack\",\"time\":\"13:45:00.250\"}}"
+        )
+        .unwrap();
+        let mut replies = BufReader::new(client.try_clone().unwrap()).lines();
+        assert_eq!(replies.next().unwrap().unwrap(), "{\"ok\":true}");
+        assert!(replies.next().unwrap().unwrap().starts_with("{\"error\":"));
+        assert_eq!(replies.next().unwrap().unwrap(), "{\"ok\":true}");
+
+        let note = next_event(&inbox);
+        assert_eq!(
+            note.command,
+            RemoteCommand::Note {
+                text: "Gear up".into(),
+                at: None
+            }
+        );
+        assert_eq!(note.source, "sim");
+        let hack = next_event(&inbox);
+        assert_eq!(hack.source, SOCKET_SOURCE);
+        assert!(matches!(hack.command, RemoteCommand::TimeHack { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_file_is_replaced_when_stale_and_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rtnt.sock");
+        drop(UnixListener::bind(&path).unwrap());
+        let mut inbox = Inbox::default();
+        inbox.listen_socket(&path).unwrap();
+        assert!(Inbox::default().listen_socket(&path).is_err());
+        drop(inbox);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn other_files_are_not_replaced_by_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.csv");
+        std::fs::write(&path, "keep").unwrap();
+        let err = Inbox::default().listen_socket(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep");
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+/// How long informational messages stay visible in the status line.
+const INFO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Severity of a [`StatusMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    /// Confirmation that an operation succeeded.
+    Info,
+    /// An operation failed and the user should act on it.
+    Error,
+}
+
+/// A message reporting the outcome of an operation to the user.
+///
+/// Informational messages expire on their own, while errors stay visible until
+/// the next key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusMessage {
+    /// Severity of the message.
+    pub kind: StatusKind,
+    /// Text shown in the status line.
+    pub text: String,
+    created: Instant,
+}
+
+impl StatusMessage {
+    /// Creates a message stamped with the current time.
+    #[must_use]
+    pub fn new(kind: StatusKind, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+            created: Instant::now(),
+        }
+    }
+
+    /// Returns `true` once an informational message should no longer be shown.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.kind == StatusKind::Info && self.created.elapsed() >= INFO_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_info_messages_expire() {
+        let mut info = StatusMessage::new(StatusKind::Info, "saved");
+        let mut error = StatusMessage::new(StatusKind::Error, "failed");
+        assert!(!info.is_expired());
+        let past = Instant::now().checked_sub(INFO_TIMEOUT).unwrap();
+        info.created = past;
+        error.created = past;
+        assert!(info.is_expired());
+        assert!(!error.is_expired());
+    }
+}
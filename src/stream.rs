@@ -0,0 +1,269 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+use ulid::Ulid;
+
+use crate::journal::Change;
+use crate::Entry;
+
+/// A change to the log as written to the event stream, one JSON object per line.
+///
+/// Entries are identified by their persistent [`Entry::id`], which stays the
+/// same while they are edited or moved and across sessions, so consumers can
+/// apply updates instead of appending duplicates.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum StreamEvent<'a> {
+    /// An entry was added at `index`.
+    Insert {
+        id: Ulid,
+        index: usize,
+        entry: &'a Entry,
+    },
+    /// The entry with `id` was edited.
+    Update { id: Ulid, entry: &'a Entry },
+    /// The entry with `id` was deleted.
+    Remove { id: Ulid },
+    /// The entry with `id` now sits at `index`.
+    Move { id: Ulid, index: usize },
+    /// The whole log was replaced; consumers should discard what they have.
+    Reset { entries: &'a [Entry] },
+    /// The clock was set so that it read `reference` when the clock source
+    /// read `system`, or was returned to the clock source if both are absent.
+    TimeHack {
+        #[serde(with = "crate::zone::utc::option")]
+        reference: Option<DateTime<Local>>,
+        #[serde(with = "crate::zone::utc::option")]
+        system: Option<DateTime<Local>>,
+    },
+}
+
+/// Writes log changes as JSON Lines to a file, FIFO or file descriptor.
+///
+/// Lines are written by a background thread so a slow reader never stalls
+/// the UI. A FIFO is opened once a reader attaches and reopened if the reader
+/// goes away.
+#[derive(Debug)]
+pub(crate) struct EventStream {
+    sender: Option<Sender<String>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl EventStream {
+    /// Starts streaming to `path` and writes a snapshot of `entries`.
+    ///
+    /// Regular files are created if needed and appended to. Opening them is
+    /// done immediately so that errors are reported here.
+    pub(crate) fn open(path: &Path, entries: &[Entry]) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let file = if is_fifo(path) {
+            None
+        } else {
+            Some(open_sink(path)?)
+        };
+        // Waiting for the writer only makes sense when it cannot be blocked
+        // on a FIFO without a reader.
+        let join = file.is_some();
+        let path = path.to_path_buf();
+        let handle = std::thread::spawn(move || write_lines(&path, file, &receiver));
+        let stream = Self {
+            sender: Some(sender),
+            writer: join.then_some(handle),
+        };
+        stream.reset(entries);
+        Ok(stream)
+    }
+
+    /// Records `change` before it is applied to `entries` and emits the
+    /// matching event.
+    pub(crate) fn record(&self, change: &Change, entries: &[Entry]) {
+        let len = entries.len();
+        match change {
+            Change::Insert { index, entry } => self.emit(&StreamEvent::Insert {
+                id: entry.id(),
+                index: (*index).min(len),
+                entry,
+            }),
+            Change::Update { index, entry } => {
+                if let Some(old) = entries.get(*index) {
+                    self.emit(&StreamEvent::Update {
+                        id: old.id(),
+                        entry,
+                    });
+                }
+            }
+            Change::Remove { index } => {
+                if let Some(old) = entries.get(*index) {
+                    self.emit(&StreamEvent::Remove { id: old.id() });
+                }
+            }
+            Change::Move { from, to } => {
+                if let (Some(moved), true) = (entries.get(*from), *to < len) {
+                    self.emit(&StreamEvent::Move {
+                        id: moved.id(),
+                        index: *to,
+                    });
+                }
+            }
+            Change::Reset { entries } => self.reset(entries),
+        }
+    }
+
+    /// Emits the current time hack, or its removal.
+    pub(crate) fn time_hack(&self, hack: Option<(DateTime<Local>, DateTime<Local>)>) {
+        self.emit(&StreamEvent::TimeHack {
+            reference: hack.map(|(reference, _)| reference),
+            system: hack.map(|(_, system)| system),
+        });
+    }
+
+    /// Tells consumers to replace what they have with `entries`.
+    pub(crate) fn reset(&self, entries: &[Entry]) {
+        self.emit(&StreamEvent::Reset { entries });
+    }
+
+    fn emit(&self, event: &StreamEvent<'_>) {
+        match serde_json::to_string(event) {
+            Ok(line) => {
+                if let Some(sender) = &self.sender {
+                    // The writer only stops when it can no longer write, which it
+                    // has already logged.
+                    let _ = sender.send(line);
+                }
+            }
+            Err(e) => log::error!("Failed to encode stream event: {e}"),
+        }
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
+}
+
+fn open_sink(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Writes each received line to `path`, reopening it after a write fails.
+fn write_lines(path: &Path, mut file: Option<File>, lines: &Receiver<String>) {
+    for line in lines {
+        if file.is_none() {
+            match open_sink(path) {
+                Ok(f) => file = Some(f),
+                Err(e) => {
+                    log::warn!("Cannot open event stream {}: {e}", path.display());
+                    continue;
+                }
+            }
+        }
+        if let Some(f) = &mut file {
+            if let Err(e) = writeln!(f, "{line}").and_then(|()| f.flush()) {
+                log::warn!("Event stream {} closed: {e}", path.display());
+                file = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Note, Section};
+    use chrono::TimeZone;
+
+    fn read_events(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn edits_reference_stable_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let mut entries = vec![Entry::Section(Section::new("Intro"))];
+        let stream = EventStream::open(&path, &entries).unwrap();
+
+        let note = Note::new(time, "first");
+        let edited = Note {
+            text: "edited".into(),
+            ..note.clone()
+        };
+        let note = Entry::Note(note);
+        let changes = [
+            Change::Insert {
+                index: 0,
+                entry: note,
+            },
+            Change::Update {
+                index: 0,
+                entry: Entry::Note(edited),
+            },
+            Change::Move { from: 0, to: 1 },
+            Change::Remove { index: 0 },
+        ];
+        for change in changes {
+            stream.record(&change, &entries);
+            change.apply(&mut entries);
+        }
+        drop(stream);
+
+        let events = read_events(&path);
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|e| e["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["reset", "insert", "update", "move", "remove"]);
+        let section_id = &events[0]["entries"][0]["Section"]["id"];
+        let note_id = &events[1]["id"];
+        assert_eq!(note_id, &events[1]["entry"]["Note"]["id"]);
+        assert_ne!(section_id, note_id);
+        assert_eq!(&events[2]["id"], note_id);
+        assert_eq!(events[2]["entry"]["Note"]["text"], "edited");
+        assert_eq!(&events[3]["id"], note_id);
+        assert_eq!(&events[4]["id"], section_id);
+    }
+
+    #[test]
+    fn ids_survive_a_new_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let entries = vec![Entry::Section(Section::new("Loaded"))];
+        drop(EventStream::open(&path, &entries).unwrap());
+        let stream = EventStream::open(&path, &entries).unwrap();
+        stream.record(&Change::Remove { index: 0 }, &entries);
+        stream.time_hack(None);
+        drop(stream);
+        let events = read_events(&path);
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|e| e["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["reset", "reset", "remove", "time_hack"]);
+        assert_eq!(events[2]["id"], entries[0].id().to_string());
+        assert!(events[3]["reference"].is_null());
+    }
+}
@@ -97,6 +97,7 @@ pub struct Theme {
     pub editing_fg: Color,
     pub editing_title: Color,
     pub overlay_text: Color,
+    pub error_fg: Color,
 }
 
 impl Theme {
@@ -122,6 +123,7 @@ impl Theme {
             editing_fg: secondary,
             editing_title: primary,
             overlay_text: primary,
+            error_fg: Color::LightRed,
         }
     }
 }
@@ -143,6 +145,7 @@ impl ThemeName {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for ThemeName {
     fn default() -> Self {
         Self::Default
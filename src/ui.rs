@@ -16,7 +16,9 @@ use std::io::{self, Stdout};
 use std::time::{Duration, Instant};
 
 use crate::key_utils::key_to_string;
-use crate::{Action, App, Entry, InputMode, ThemeName};
+use crate::{
+    Action, App, DisplayZone, Entry, ExportFormat, InputMode, StatusKind, Theme, ThemeName,
+};
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -94,6 +96,7 @@ pub fn run_ui(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -
     let tick_rate = Duration::from_millis(200);
     let mut last_tick = Instant::now();
     loop {
+        app.process_remote();
         terminal.draw(|f| draw(f, &app))?;
         let timeout = tick_rate.saturating_sub(last_tick.elapsed());
         if crossterm::event::poll(timeout)? {
@@ -102,11 +105,11 @@ pub fn run_ui(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -
                 if key.kind != event::KeyEventKind::Press {
                     continue;
                 }
-                if key.code == app.keys.quit {
-                    break;
-                }
             }
             app.handle_event(&ev).ok();
+            if app.should_quit() {
+                break;
+            }
         }
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
@@ -115,6 +118,19 @@ pub fn run_ui(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -
     Ok(app)
 }
 
+/// Labels an entry created by another program with the program's name.
+fn source_span<'a>(source: Option<&'a str>, theme: &Theme) -> Span<'a> {
+    match source {
+        Some(source) => Span::styled(
+            format!("[{source}] "),
+            Style::default()
+                .fg(theme.note_fg)
+                .add_modifier(Modifier::DIM),
+        ),
+        None => Span::raw(""),
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
     let theme = app.theme();
@@ -134,26 +150,56 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
     let notes: Vec<ListItem> = app
         .entries
         .iter()
-        .map(|e| match e {
+        .enumerate()
+        .map(|(i, e)| match e {
             Entry::Note(n) => ListItem::new(Line::from(vec![
                 Span::styled(
-                    n.timestamp.format("%H:%M:%S%.3f").to_string(),
+                    app.display_zone.format(n.timestamp, "%H:%M:%S%.3f"),
+                    Style::default()
+                        .fg(theme.timestamp_fg)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    match (n.original_timestamp.is_some(), app.is_out_of_order(i)) {
+                        (_, true) => "!",
+                        (true, false) => "*",
+                        (false, false) => " ",
+                    },
                     Style::default()
                         .fg(theme.timestamp_fg)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - "),
-                Span::styled(&n.text, Style::default().fg(theme.note_fg)),
+                source_span(n.source.as_deref(), &theme),
+                if n.pending {
+                    Span::styled(
+                        "<pending>",
+                        Style::default()
+                            .fg(theme.note_fg)
+                            .add_modifier(Modifier::DIM | Modifier::ITALIC),
+                    )
+                } else {
+                    Span::styled(&n.text, Style::default().fg(theme.note_fg))
+                },
+            ])),
+            Entry::Section(s) => ListItem::new(Line::from(vec![
+                source_span(s.source.as_deref(), &theme),
+                Span::styled(
+                    &s.title,
+                    Style::default()
+                        .fg(theme.section_fg)
+                        .add_modifier(Modifier::BOLD),
+                ),
             ])),
-            Entry::Section(s) => ListItem::new(Line::from(vec![Span::styled(
-                &s.title,
-                Style::default()
-                    .fg(theme.section_fg)
-                    .add_modifier(Modifier::BOLD),
-            )])),
         })
         .collect();
 
+    let pending = app.pending_count();
+    let notes_title = if pending > 0 {
+        format!("Notes ({pending} pending)")
+    } else {
+        "Notes".to_string()
+    };
     let notes_list = List::new(notes)
         .block(
             Block::default()
@@ -161,7 +207,7 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
                 .border_type(BorderType::Thick)
                 .border_style(Style::default().fg(theme.notes_border))
                 .title(Span::styled(
-                    "Notes",
+                    notes_title,
                     Style::default().fg(theme.notes_title),
                 )),
         )
@@ -183,7 +229,7 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
     let input_title = match app.mode() {
         InputMode::EditingNote | InputMode::EditingExistingNote => {
             if let Some(time) = app.note_time() {
-                format!("Note - {}", time.format("%H:%M:%S%.3f"))
+                format!("Note - {}", app.display_zone.format(time, "%H:%M:%S%.3f"))
             } else {
                 "Note".to_string()
             }
@@ -195,10 +241,23 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
         InputMode::NewFile => format!("New File - {}", app.save_dir.display()),
         InputMode::KeyBindings => "Key Bindings".to_string(),
         InputMode::ThemeSelect => "Select Theme".to_string(),
-        InputMode::TimeHack => "Time Hack - HH:MM:SS[.mmm]".to_string(),
+        InputMode::TimeHack => {
+            "Time Hack - HH:MM:SS[.mmm][Z|+HH:MM], DDD:HH:MM:SS or ISO 8601".to_string()
+        }
         InputMode::KeyCapture => "Set Key".to_string(),
         InputMode::ConfirmReplace => "Confirm".to_string(),
         InputMode::BindWarning => "Warning".to_string(),
+        InputMode::RecoverSession => "Recover Session".to_string(),
+        InputMode::ConfirmDelete => "Delete".to_string(),
+        InputMode::EditingTimestamp => "Timestamp - HH:MM:SS[.mmm] or +/-N[ms|s|m|h]".to_string(),
+        InputMode::Metadata => "Session Info".to_string(),
+        InputMode::ConfirmQuit => "Quit".to_string(),
+        InputMode::Exporting => format!("Export {}", app.export_format.display_name()),
+        InputMode::EditingMetadata => match app.metadata_selected {
+            0 => "Session Title".to_string(),
+            1 => "Operator".to_string(),
+            _ => "Field - key=value".to_string(),
+        },
         InputMode::Normal => "Input".to_string(),
     };
 
@@ -212,6 +271,9 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
             | InputMode::Loading
             | InputMode::NewFile
             | InputMode::TimeHack
+            | InputMode::EditingTimestamp
+            | InputMode::EditingMetadata
+            | InputMode::Exporting
     );
 
     let input_block = Block::default()
@@ -242,6 +304,9 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
             | InputMode::Loading
             | InputMode::NewFile
             | InputMode::TimeHack
+            | InputMode::EditingTimestamp
+            | InputMode::EditingMetadata
+            | InputMode::Exporting
     ) {
         let offset = u16::try_from(app.cursor()).unwrap_or(u16::MAX);
         f.set_cursor_position((
@@ -251,7 +316,15 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
     }
     f.render_widget(input, chunks[1]);
 
-    let help_spans = if matches!(app.mode(), InputMode::TimeHack) {
+    let help_spans = if let Some(status) = app.status() {
+        let style = match status.kind {
+            StatusKind::Info => Style::default().fg(theme.help_key),
+            StatusKind::Error => Style::default()
+                .fg(theme.error_fg)
+                .add_modifier(Modifier::BOLD),
+        };
+        vec![Span::styled(status.text.as_str(), style)]
+    } else if matches!(app.mode(), InputMode::TimeHack) {
         vec![
             Span::styled("Enter", Style::default().fg(theme.help_key)),
             Span::styled(":Begin time hack ", Style::default().fg(theme.help_desc)),
@@ -281,11 +354,61 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
                 Style::default().fg(theme.help_key),
             ),
             Span::styled(":Section ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.mark),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Mark ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.describe),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Describe ", Style::default().fg(theme.help_desc)),
             Span::styled(
                 key_to_string(app.keys.edit),
                 Style::default().fg(theme.help_key),
             ),
             Span::styled(":Edit ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.edit_time),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Time ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.delete),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Delete ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.move_up),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.move_down),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Move ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.insert_above),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.insert_below),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Insert ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.undo),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Undo ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.redo),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Redo ", Style::default().fg(theme.help_desc)),
             Span::styled(
                 key_to_string(app.keys.up),
                 Style::default().fg(theme.help_key),
@@ -316,6 +439,11 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
                 Style::default().fg(theme.help_key),
             ),
             Span::styled(":Hack ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                key_to_string(app.keys.metadata),
+                Style::default().fg(theme.help_key),
+            ),
+            Span::styled(":Info ", Style::default().fg(theme.help_desc)),
             Span::styled(
                 key_to_string(app.keys.quit),
                 Style::default().fg(theme.help_key),
@@ -324,8 +452,52 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
         ]
     };
     let now = app.current_time();
-    let time_label = format!("{} Source: {}", now.format("%H:%M:%S"), app.time_source());
-    let time_width = u16::try_from(time_label.len()).unwrap_or(0);
+    let unsaved = if app.is_dirty() { "Unsaved " } else { "" };
+    // Only a failing source is called out, to keep the status line short.
+    let health = app.clock_health();
+    let health = if health.is_good() {
+        String::new()
+    } else {
+        format!(" ({health})")
+    };
+    let mut time_spans = vec![Span::styled(
+        unsaved,
+        Style::default()
+            .fg(theme.error_fg)
+            .add_modifier(Modifier::BOLD),
+    )];
+    // Zones are named once there is more than one clock or it is not local.
+    let labelled = app.display_zone != DisplayZone::Local || app.second_zone.is_some();
+    for (i, zone) in std::iter::once(app.display_zone)
+        .chain(app.second_zone)
+        .enumerate()
+    {
+        if i > 0 {
+            time_spans.push(Span::raw(" "));
+        }
+        time_spans.push(Span::styled(
+            zone.format(now, "%H:%M:%S"),
+            Style::default().fg(theme.help_key),
+        ));
+        if labelled {
+            time_spans.push(Span::styled(
+                format!(" {}", zone.label(now)),
+                Style::default().fg(theme.help_desc),
+            ));
+        }
+    }
+    time_spans.extend([
+        Span::styled(" Source: ", Style::default().fg(theme.help_desc)),
+        Span::styled(app.time_source(), Style::default().fg(theme.help_key)),
+        Span::styled(
+            health,
+            Style::default()
+                .fg(theme.error_fg)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    let time_line = Line::from(time_spans);
+    let time_width = u16::try_from(time_line.width()).unwrap_or(0);
     let left_width = chunks[2].width.saturating_sub(time_width);
     let areas = Layout::default()
         .direction(Direction::Horizontal)
@@ -337,25 +509,23 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
 
     let help = Paragraph::new(Line::from(help_spans));
     f.render_widget(help, areas[0]);
-    let time_line = Line::from(vec![
-        Span::styled(
-            now.format("%H:%M:%S").to_string(),
-            Style::default().fg(theme.help_key),
-        ),
-        Span::styled(" Source: ", Style::default().fg(theme.help_desc)),
-        Span::styled(app.time_source(), Style::default().fg(theme.help_key)),
-    ]);
     let time_widget = Paragraph::new(time_line).alignment(Alignment::Right);
     f.render_widget(time_widget, areas[1]);
 
     if matches!(app.mode(), InputMode::FileMenu) {
         let area = centered_rect(40, 40, f.area());
         f.render_widget(Clear, area);
-        let items = vec![
+        let mut items = vec![
             ListItem::new("New File"),
             ListItem::new("Save File"),
             ListItem::new("Load File"),
         ];
+        items.extend(
+            ExportFormat::ALL
+                .iter()
+                .map(|f| ListItem::new(format!("Export {}", f.display_name()))),
+        );
+        items.push(ListItem::new("Re-time Notes Between Hacks"));
         let mut state = ListState::default();
         state.select(Some(app.file_menu_selected));
         let block = Block::default()
@@ -409,6 +579,10 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
         f.render_stateful_widget(list, area, &mut state);
     }
 
+    if matches!(app.mode(), InputMode::Metadata | InputMode::EditingMetadata) {
+        draw_metadata(f, app, &theme);
+    }
+
     if matches!(app.mode(), InputMode::KeyBindings) {
         let area = centered_rect(60, 60, f.area());
         f.render_widget(Clear, area);
@@ -517,6 +691,110 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
             );
             f.render_widget(msg, area);
         }
+    } else if matches!(app.mode(), InputMode::ConfirmDelete) {
+        let label = match app.selected().and_then(|i| app.entries.get(i)) {
+            Some(Entry::Note(n)) => {
+                format!(
+                    "note {} - {}",
+                    app.display_zone.format(n.timestamp, "%H:%M:%S%.3f"),
+                    n.text
+                )
+            }
+            Some(Entry::Section(s)) => format!("section {}", s.title),
+            None => "entry".to_string(),
+        };
+        let area = centered_rect(60, 20, f.area());
+        f.render_widget(Clear, area);
+        let msg = Paragraph::new(vec![
+            Line::from(Span::styled(
+                format!("Delete {label}?"),
+                Style::default().fg(theme.overlay_text),
+            )),
+            Line::from(vec![
+                Span::styled("Enter", Style::default().fg(theme.help_key)),
+                Span::styled(":Delete ", Style::default().fg(theme.help_desc)),
+                Span::styled(
+                    key_to_string(app.keys.cancel),
+                    Style::default().fg(theme.help_key),
+                ),
+                Span::styled(":Cancel", Style::default().fg(theme.help_desc)),
+            ]),
+        ])
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.overlay_border))
+                .title(Span::styled(
+                    "Confirm Delete",
+                    Style::default().fg(theme.overlay_title),
+                ))
+                .style(Style::default().bg(theme.overlay_bg)),
+        );
+        f.render_widget(msg, area);
+    } else if matches!(app.mode(), InputMode::ConfirmQuit) {
+        let save_label = match app.file_path().and_then(|p| p.file_name()) {
+            Some(name) => format!("Save to {} and quit", name.to_string_lossy()),
+            None => "Save and quit".to_string(),
+        };
+        let area = centered_rect(40, 30, f.area());
+        f.render_widget(Clear, area);
+        let items = vec![
+            ListItem::new(save_label),
+            ListItem::new("Quit without saving"),
+            ListItem::new("Cancel"),
+        ];
+        let mut state = ListState::default();
+        state.select(Some(app.quit_selected));
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(
+                "Unsaved Changes",
+                Style::default().fg(theme.overlay_title),
+            ))
+            .border_type(BorderType::Plain)
+            .border_style(Style::default().fg(theme.overlay_border))
+            .style(Style::default().bg(theme.overlay_bg));
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(theme.overlay_highlight_bg)
+                .fg(theme.overlay_highlight_fg)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(list, area, &mut state);
+    } else if matches!(app.mode(), InputMode::RecoverSession) {
+        let count = app.recovered_entries().map_or(0, <[_]>::len);
+        let area = centered_rect(60, 20, f.area());
+        f.render_widget(Clear, area);
+        let msg = Paragraph::new(vec![
+            Line::from(Span::styled(
+                format!("The previous session did not exit cleanly. Restore {count} entries?"),
+                Style::default().fg(theme.overlay_text),
+            )),
+            Line::from(vec![
+                Span::styled("Enter", Style::default().fg(theme.help_key)),
+                Span::styled(":Restore ", Style::default().fg(theme.help_desc)),
+                Span::styled(
+                    key_to_string(app.keys.cancel),
+                    Style::default().fg(theme.help_key),
+                ),
+                Span::styled(":Discard", Style::default().fg(theme.help_desc)),
+            ]),
+        ])
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.overlay_border))
+                .title(Span::styled(
+                    "Recover Session",
+                    Style::default().fg(theme.overlay_title),
+                ))
+                .style(Style::default().bg(theme.overlay_bg)),
+        );
+        f.render_widget(msg, area);
     } else if matches!(app.mode(), InputMode::BindWarning) {
         let area = centered_rect(60, 20, f.area());
         f.render_widget(Clear, area);
@@ -539,3 +817,84 @@ fn draw(f: &mut ratatui::Frame<'_>, app: &App) {
         f.render_widget(msg, area);
     }
 }
+
+fn draw_metadata(f: &mut ratatui::Frame<'_>, app: &App, theme: &crate::Theme) {
+    let area = centered_rect(60, 60, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(
+            "Session Info",
+            Style::default().fg(theme.overlay_title),
+        ))
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.overlay_border))
+        .style(Style::default().bg(theme.overlay_bg));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(inner);
+
+    let meta = &app.metadata;
+    let drift = app.clock_drift().map_or_else(
+        || "needs two time hacks".to_string(),
+        |rate| {
+            format!(
+                "{:+.1} ppm ({:+.3} s per hour, {} hacks)",
+                rate * 1e6,
+                rate * 3600.0,
+                app.time_hacks().len()
+            )
+        },
+    );
+    let source = match &meta.time_source {
+        Some(saved) if saved != app.time_source() => {
+            format!("{} (saved with {saved})", app.time_source())
+        }
+        _ => app.time_source().to_string(),
+    };
+    let header = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("Created: ", Style::default().fg(theme.help_desc)),
+            Span::styled(
+                app.display_zone.format(meta.created, "%Y-%m-%d %H:%M:%S"),
+                Style::default().fg(theme.overlay_text),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Time source: ", Style::default().fg(theme.help_desc)),
+            Span::styled(source, Style::default().fg(theme.overlay_text)),
+        ]),
+        Line::from(vec![
+            Span::styled("Clock drift: ", Style::default().fg(theme.help_desc)),
+            Span::styled(drift, Style::default().fg(theme.overlay_text)),
+        ]),
+    ]);
+    f.render_widget(header, parts[0]);
+
+    let row = |label: &str, value: &str| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!("{label}: "), Style::default().fg(theme.help_desc)),
+            Span::styled(value.to_string(), Style::default().fg(theme.overlay_text)),
+        ]))
+    };
+    let mut items = vec![row("Title", &meta.title), row("Operator", &meta.operator)];
+    items.extend(meta.fields.iter().map(|(k, v)| row(k, v)));
+    items.push(ListItem::new(Span::styled(
+        "<add field>",
+        Style::default()
+            .fg(theme.overlay_text)
+            .add_modifier(Modifier::ITALIC),
+    )));
+    let mut state = ListState::default();
+    state.select(Some(app.metadata_selected));
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(theme.overlay_highlight_bg)
+            .fg(theme.overlay_highlight_fg)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(list, parts[1], &mut state);
+}
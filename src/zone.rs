@@ -0,0 +1,236 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Time zone timestamps are shown and entered in.
+///
+/// Timestamps are kept as instants, so changing the zone only changes how
+/// they are displayed. Saved files always store UTC, see [`utc`].
+///
+/// # Example
+/// ```
+/// use chrono::{Local, TimeZone, Utc};
+/// use real_time_note_taker::DisplayZone;
+///
+/// let zone: DisplayZone = "Europe/Berlin".parse().unwrap();
+/// let time = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap().with_timezone(&Local);
+/// assert_eq!(zone.format(time, "%H:%M %Z"), "11:00 CEST");
+/// assert_eq!(DisplayZone::Utc.format(time, "%H:%M"), "09:00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DisplayZone {
+    /// The zone of the machine running `rtnt`.
+    #[default]
+    Local,
+    /// Coordinated Universal Time.
+    Utc,
+    /// A zone from the IANA time zone database, such as `America/Chicago`.
+    Named(Tz),
+}
+
+impl DisplayZone {
+    /// Formats `time` in this zone with a `strftime` style format.
+    #[must_use]
+    pub fn format(self, time: DateTime<Local>, format: &str) -> String {
+        match self {
+            Self::Local => time.format(format).to_string(),
+            Self::Utc => time.with_timezone(&Utc).format(format).to_string(),
+            Self::Named(tz) => time.with_timezone(&tz).format(format).to_string(),
+        }
+    }
+
+    /// Returns a short name of the zone at `time`, such as `UTC` or `CEST`,
+    /// or `local` for the local zone.
+    #[must_use]
+    pub fn label(self, time: DateTime<Local>) -> String {
+        match self {
+            Self::Local => "local".into(),
+            _ => self.format(time, "%Z"),
+        }
+    }
+
+    /// Returns the instant at which clocks in this zone read `time`.
+    ///
+    /// The earlier instant is used when clocks are set back and `time`
+    /// occurs twice; `None` is returned when it is skipped by a change to
+    /// summer time.
+    #[must_use]
+    pub fn from_wall_clock(self, time: NaiveDateTime) -> Option<DateTime<Local>> {
+        match self {
+            Self::Local => Local.from_local_datetime(&time).earliest(),
+            Self::Utc => Some(Utc.from_utc_datetime(&time).with_timezone(&Local)),
+            Self::Named(tz) => tz
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.with_timezone(&Local)),
+        }
+    }
+
+    /// Returns the date and time clocks in this zone read at `time`.
+    #[must_use]
+    pub fn wall_clock(self, time: DateTime<Local>) -> NaiveDateTime {
+        match self {
+            Self::Local => time.naive_local(),
+            Self::Utc => time.naive_utc(),
+            Self::Named(tz) => time.with_timezone(&tz).naive_local(),
+        }
+    }
+}
+
+impl FromStr for DisplayZone {
+    type Err = String;
+
+    /// Parses `local`, `UTC` (or `Z`) or an IANA zone name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            Ok(Self::Local)
+        } else if s.eq_ignore_ascii_case("utc") || s.eq_ignore_ascii_case("z") {
+            Ok(Self::Utc)
+        } else {
+            Tz::from_str_insensitive(s)
+                .map(Self::Named)
+                .map_err(|_| format!("unknown time zone `{s}`"))
+        }
+    }
+}
+
+impl fmt::Display for DisplayZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => f.write_str("local"),
+            Self::Utc => f.write_str("UTC"),
+            Self::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+impl TryFrom<String> for DisplayZone {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<DisplayZone> for String {
+    fn from(zone: DisplayZone) -> Self {
+        zone.to_string()
+    }
+}
+
+/// Serializes timestamps in UTC so that files written on machines in
+/// different zones can be compared and merged line by line.
+///
+/// Any offset is accepted when reading. Use with `#[serde(with = ...)]`.
+pub(crate) mod utc {
+    use chrono::{DateTime, Local, Utc};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        time: &DateTime<Local>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        time.with_timezone(&Utc).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Local>, D::Error> {
+        DateTime::deserialize(deserializer)
+    }
+
+    /// The same for optional timestamps.
+    pub(crate) mod option {
+        use chrono::{DateTime, Local, Utc};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        #[allow(clippy::ref_option)]
+        pub(crate) fn serialize<S: Serializer>(
+            time: &Option<DateTime<Local>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            time.map(|t| t.with_timezone(&Utc)).serialize(serializer)
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<Local>>, D::Error> {
+            Option::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn instant() -> DateTime<Local> {
+        Utc.with_ymd_and_hms(2024, 1, 15, 23, 30, 0)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    #[test]
+    fn parses_and_displays_zones() {
+        for (input, shown) in [
+            ("local", "local"),
+            ("UTC", "UTC"),
+            ("z", "UTC"),
+            ("america/new_york", "America/New_York"),
+            (" Asia/Kolkata ", "Asia/Kolkata"),
+        ] {
+            let zone: DisplayZone = input.parse().unwrap();
+            assert_eq!(zone.to_string(), shown);
+            assert_eq!(zone.to_string().parse::<DisplayZone>().unwrap(), zone);
+        }
+        assert!("Mars/Olympus".parse::<DisplayZone>().is_err());
+    }
+
+    #[test]
+    fn shows_and_reads_wall_clock_times() {
+        let tokyo: DisplayZone = "Asia/Tokyo".parse().unwrap();
+        assert_eq!(tokyo.format(instant(), "%F %T"), "2024-01-16 08:30:00");
+        assert_eq!(tokyo.label(instant()), "JST");
+        assert_eq!(DisplayZone::Utc.label(instant()), "UTC");
+        assert_eq!(DisplayZone::Local.label(instant()), "local");
+        for zone in [DisplayZone::Local, DisplayZone::Utc, tokyo] {
+            let wall = zone.wall_clock(instant());
+            assert_eq!(zone.from_wall_clock(wall), Some(instant()), "{zone}");
+        }
+        let skipped = NaiveDate::from_ymd_opt(2024, 3, 10)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let chicago: DisplayZone = "America/Chicago".parse().unwrap();
+        assert_eq!(chicago.from_wall_clock(skipped), None);
+    }
+
+    #[test]
+    fn serializes_timestamps_in_utc() {
+        #[derive(Serialize, Deserialize)]
+        struct Stamp {
+            #[serde(with = "utc")]
+            at: DateTime<Local>,
+            #[serde(with = "utc::option")]
+            original: Option<DateTime<Local>>,
+        }
+        let json = serde_json::to_string(&Stamp {
+            at: instant(),
+            original: Some(instant()),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"at":"2024-01-15T23:30:00Z","original":"2024-01-15T23:30:00Z"}"#
+        );
+        let read: Stamp =
+            serde_json::from_str(r#"{"at":"2024-01-16T08:30:00+09:00","original":null}"#).unwrap();
+        assert_eq!(read.at, instant());
+        assert_eq!(read.original, None);
+    }
+}
@@ -18,3 +18,220 @@ fn runs_verbose() {
     let mut cmd = Command::cargo_bin("rtnt").unwrap();
     cmd.args(["-v", "--help"]).assert().success();
 }
+
+#[test]
+fn exports_markdown() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("log.csv");
+    std::fs::write(
+        &input,
+        "section,,Takeoff\nnote,2024-05-01T09:00:00+00:00,Brakes released\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("export")
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("## Takeoff"))
+        .stdout(predicates::str::contains("Brakes released"));
+
+    let output = dir.path().join("log.md");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("export")
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success();
+    assert!(std::fs::read_to_string(&output)
+        .unwrap()
+        .contains("## Takeoff"));
+}
+
+#[test]
+fn export_fails_for_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("export")
+        .arg(dir.path().join("missing.csv"))
+        .assert()
+        .code(66)
+        .stderr(predicates::str::contains("file not found"));
+}
+
+fn write_log(dir: &std::path::Path) -> std::path::PathBuf {
+    let input = dir.join("log.csv");
+    std::fs::write(
+        &input,
+        "section,,Takeoff\nnote,2024-05-01T09:00:00+00:00,Brakes released\n",
+    )
+    .unwrap();
+    input
+}
+
+#[test]
+fn exports_requested_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = write_log(dir.path());
+    for (format, expected) in [
+        ("html", "<a href=\"#section-0\">Takeoff</a></h2>"),
+        ("json", "\"rtnt\": 1"),
+        ("jsonl", "\"title\":\"Takeoff\"}}"),
+        ("csv", "#rtnt,1"),
+    ] {
+        let mut cmd = Command::cargo_bin("rtnt").unwrap();
+        cmd.args(["export", "--format", format])
+            .arg(&input)
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(expected));
+    }
+}
+
+#[test]
+fn exports_in_requested_zone() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = write_log(dir.path());
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["export", "--zone", "America/Chicago"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("`04:00:00.000` Brakes released"))
+        .stdout(predicates::str::contains("**Time zone:** America/Chicago"));
+}
+
+#[test]
+fn converts_in_requested_zone() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = write_log(dir.path());
+    let output = dir.path().join("log.md");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["--zone", "America/Chicago", "convert"])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .success();
+    let markdown = std::fs::read_to_string(&output).unwrap();
+    assert!(markdown.contains("`04:00:00.000` Brakes released"));
+    assert!(markdown.contains("**Time zone:** America/Chicago"));
+}
+
+#[test]
+fn converts_by_output_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = write_log(dir.path());
+    let json = dir.path().join("log.json");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("convert").arg(&input).arg(&json).assert().success();
+
+    let html = dir.path().join("log.html");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("convert").arg(&json).arg(&html).assert().success();
+    assert!(std::fs::read_to_string(&html)
+        .unwrap()
+        .contains("Brakes released"));
+}
+
+#[test]
+fn convert_keeps_saved_time_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("gps.csv");
+    std::fs::write(
+        &input,
+        "#rtnt,1\n\
+         type,timestamp,text,original_timestamp,pending,key\n\
+         meta,,GPS,,,time_source\n\
+         note,2024-05-01T09:00:00+00:00,Brakes released,,false,\n",
+    )
+    .unwrap();
+    let json = dir.path().join("gps.json");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("convert").arg(&input).arg(&json).assert().success();
+    assert!(std::fs::read_to_string(&json)
+        .unwrap()
+        .contains("\"time_source\": \"GPS\""));
+
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("export")
+        .arg(&json)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("**Time source:** GPS"));
+}
+
+#[test]
+fn convert_rejects_unknown_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = write_log(dir.path());
+    let output = dir.path().join("log.xyz");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("convert")
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .code(64)
+        .stderr(predicates::str::contains("unknown file format"));
+    assert!(!output.exists());
+}
+
+#[test]
+fn adds_notes_and_sections() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("log.csv");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["add", "--section", "--file"])
+        .arg(&file)
+        .arg("Start up")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["add", "--at", "2024-05-01T09:00:00Z", "--file"])
+        .arg(&file)
+        .arg("Engine start, left")
+        .assert()
+        .success();
+    let text = std::fs::read_to_string(&file).unwrap();
+    assert!(text.starts_with("#rtnt,1\n"));
+    let row = text.trim_end().strip_suffix(",,").unwrap();
+    let (row, id) = row.rsplit_once(',').unwrap();
+    assert!(row.ends_with("\"Engine start, left\",,false,,"), "{text}");
+    assert_eq!(id.len(), 26, "{text}");
+
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["export", "--format", "md"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("## Start up"))
+        .stdout(predicates::str::contains("Engine start, left"));
+}
+
+#[test]
+fn add_reads_time_of_day_in_zone() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("log.csv");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["add", "--zone", "Asia/Tokyo", "--at", "09:30:00", "--file"])
+        .arg(&file)
+        .arg("Briefing")
+        .assert()
+        .success();
+    let text = std::fs::read_to_string(&file).unwrap();
+    assert!(text.contains("T00:30:00Z,Briefing,"), "{text}");
+}
+
+#[test]
+fn add_rejects_bad_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("log.csv");
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["add", "--at", "noon", "--file"])
+        .arg(&file)
+        .arg("late")
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains("HH:MM:SS"));
+    assert!(!file.exists());
+}
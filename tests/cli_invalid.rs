@@ -5,3 +5,51 @@ fn fails_on_unknown_arg() {
     let mut cmd = Command::cargo_bin("rtnt").unwrap();
     cmd.arg("--unknown").assert().failure();
 }
+
+#[test]
+fn refuses_unreadable_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("future.csv");
+    let contents = "#rtnt,99\ntype,timestamp,text\n";
+    std::fs::write(&path, contents).unwrap();
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("--save-dir")
+        .arg(dir.path())
+        .arg("--file")
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("version 99"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+}
+
+#[test]
+fn refuses_unknown_zone() {
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["--zone", "Mars/Olympus_Mons"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown time zone"));
+}
+
+#[test]
+fn refuses_missing_gps_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.arg("--save-dir")
+        .arg(dir.path())
+        .arg("--gps")
+        .arg(dir.path().join("ttyGPS"))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("GPS source"));
+}
+
+#[test]
+fn refuses_two_clock_sources() {
+    let mut cmd = Command::cargo_bin("rtnt").unwrap();
+    cmd.args(["--gps", "udp:127.0.0.1:0", "--ntp", "127.0.0.1"])
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains("cannot be used with"));
+}